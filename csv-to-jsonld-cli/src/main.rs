@@ -45,6 +45,10 @@ enum Commands {
         /// Path to vocabulary metadata file to import
         #[arg(long, value_name = "VOCABULARY METADATA INPUT PATH")]
         import_vocab_meta: Option<StorageLocation>,
+
+        /// Export SHACL shapes for the vocabulary to a file or directory
+        #[arg(long, value_name = "SHAPES OUTPUT PATH")]
+        export_shapes: Option<StorageLocation>,
     },
     /// Generate a manifest template
     GenerateManifest {
@@ -126,6 +130,7 @@ async fn main() -> Result<()> {
             output,
             export_vocab_meta,
             import_vocab_meta,
+            export_shapes,
             ..
         } => {
             process_command(
@@ -134,6 +139,7 @@ async fn main() -> Result<()> {
                 output,
                 export_vocab_meta.clone(),
                 import_vocab_meta.clone(),
+                export_shapes.clone(),
             )
             .await
         }
@@ -146,6 +152,7 @@ async fn process_command(
     output: &Option<PathBuf>,
     export_vocab_meta: Option<StorageLocation>,
     import_vocab_meta: Option<StorageLocation>,
+    export_shapes: Option<StorageLocation>,
 ) -> Result<()> {
     let mut processing_state = ProcessingState::new();
     if strict {
@@ -185,14 +192,16 @@ async fn process_command(
     // Create and run processor
     info!("Initializing processor...");
 
-    let is_model_sequence_empty = manifest.model.sequence.is_empty();
+    let is_model_sequence_empty =
+        manifest.model.sequence.is_empty() && manifest.model.base_ontology.is_none();
 
     let processor_builder = ProcessorBuilder::from_manifest(manifest)
         .with_base_path(base_path)
         .with_instance_output_path(output_path.to_string_lossy().to_string())?
         .with_model_output_path(output_path.to_string_lossy().to_string())?
         .with_strict(strict)
-        .with_export_vocab_meta(export_vocab_meta);
+        .with_export_vocab_meta(export_vocab_meta)
+        .with_shapes_output_path(export_shapes);
 
     let processor_builder = if is_model_sequence_empty {
         tracing::info!(
@@ -33,6 +33,31 @@ pub const FULL_MANIFEST: &str = r#"{
   "@id": "your-model-id",
   "name": "Your Model Name",
   "description": "Description of your data model",
+  // Namespaces for prefixed names in model CSV cells (optional). Class IDs, property IDs and
+  // target classes written as "schema:name" use the namespace instead of the model baseIRI,
+  // and the prefixes are declared in the output contexts
+  "prefixes": {
+    "schema": "https://schema.org/",
+    "skos": "http://www.w3.org/2004/02/skos/core#"
+  },
+  // How dates, numbers and booleans are read (optional) - steps can override this with their own
+  // "parseProfile", and individual columns with "columnParseProfiles"
+  "parseProfile": {
+    // Locale supplying default date order and separators (e.g. "en-US", "de-DE")
+    "locale": "en-US",
+    // Date formats to try, in order (chrono strftime syntax)
+    "dateFormats": ["%m/%d/%Y", "%Y-%m-%d"],
+    // Timestamp formats tried after RFC 3339 for dateTime columns
+    "dateTimeFormats": ["%m/%d/%Y %H:%M", "%Y-%m-%d %H:%M:%S"],
+    // UTC offset assumed for timestamps written without one, and whether to convert them to UTC
+    "timeZone": "-05:00",
+    "normalizeToUtc": true,
+    "decimalSeparator": ".",
+    "thousandsSeparator": ",",
+    // Accepted boolean tokens (case-insensitive)
+    "trueValues": ["true", "yes", "y"],
+    "falseValues": ["false", "no", "n"]
+  },
   // Model section defines CSV paths and configuration for vocabulary/model processing
   // If no "model" section is present, default assumptions will be made about instance CSV files
   "model": {
@@ -42,6 +67,17 @@ pub const FULL_MANIFEST: &str = r#"{
     // Whether to namespace IRIs (optional)
     // When true, IRIs will include the full path structure
     "namespaceIris": true,
+    // Vocabulary for the model output (optional): "RDFS" (default) for a Fluree data model of
+    // RDFS classes and properties, or "OWL" for an OWL ontology with oneOf enumerations and
+    // cardinality restrictions
+    "outputFormat": "RDFS",
+    // Existing RDFS or OWL ontology to start the model from (optional), in Turtle (.ttl) or
+    // JSON-LD, such as an earlier model.jsonld, resolved like the sequence paths. Model CSV
+    // steps extend it, and their values win over the ontology's.
+    // Turtle 1.1 is supported except escapes inside <IRIs>; RDF-star and TriG/N3 graph blocks
+    // are rejected. JSON-LD contexts must be inline (no remote contexts or @import), and
+    // @reverse, @included, @nest and containers other than @list and @set are rejected
+    "baseOntology": "base-ontology.ttl",
     // Base path for model CSV files (optional)
     // All paths in sequence will be relative to this
     "path": "model/",
@@ -55,7 +91,9 @@ pub const FULL_MANIFEST: &str = r#"{
           "CSVImportStep",
           "BasicVocabularyStep"
         ],
-        // Map CSV columns to standard vocabulary fields
+        // Map CSV columns to standard vocabulary fields. Name and description columns can have
+        // per-language variants named with a language tag, e.g. "Class Name (fr)" or
+        // "Property Description (de)", which become language-tagged labels and comments
         "overrides": [
           {
             "column": "Class Name",
@@ -71,8 +109,18 @@ pub const FULL_MANIFEST: &str = r#"{
             // $Property.ID - The property identifier
             // $Property.Name - Human-readable name
             // $Property.Description - Property description
-            // $Property.Type - Property datatype
+            // $Property.Type - Property datatype (@id, String, Integer, Long, Float, Boolean, Date,
+            //   DateTime, Time, Duration, gYear, anyURI, langString, URI, Picklist)
             // $Property.TargetClass - Class that this property references
+            // $Property.Unit - Unit or currency of numeric values (e.g. "kg", "USD", "%", or a QUDT
+            //   IRI), read from a "Unit" column by default. Values become QUDT quantity values
+            // $Property.Required, $Property.MinCount, $Property.MaxCount - Number of values each
+            //   instance must have, read from "Required", "Min Count" and "Max Count" columns by
+            //   default and checked once all instances are loaded
+            // $Property.Pattern, $Property.MinValue, $Property.MaxValue, $Property.MinLength,
+            //   $Property.MaxLength - Rules each value must follow, read from "Pattern", "Min Value",
+            //   "Max Value", "Min Length" and "Max Length" columns by default. Patterns are regular
+            //   expressions matching the whole value; value bounds apply to numeric properties
             "mapTo": "$Property.ID"
           }
         ],
@@ -149,9 +197,55 @@ pub const FULL_MANIFEST: &str = r#"{
         ],
         // The type to assign to instances from this CSV
         "instanceType": "BasicType",
+        // Parse profiles for individual columns, e.g. dates from an EU plant
+        "columnParseProfiles": {
+          "Inspection Date": { "locale": "de-DE", "dateFormats": ["%d.%m.%Y"] }
+        },
+        // Languages of string columns, emitted as {"@value", "@language"} objects. Headers can
+        // also carry their language as a suffix, e.g. "Product Name@de"
+        "columnLanguages": {
+          "Nom du produit": "fr"
+        },
         // Columns to ignore during processing
         "ignore": [
           "IgnoreThisColumn"
+        ],
+        // Additional values to attach to every instance from this step
+        "extraItems": [
+          {
+            // Static value added to each instance
+            "mapTo": "http://example.com/terms/sourceSystem",
+            "onEntity": "INSTANCE",
+            "value": "ERP"
+          },
+          {
+            // Or take the value from a column that isn't in the model
+            "column": "Data Owner",
+            "mapTo": "http://example.com/terms/dataOwner",
+            "onEntity": "INSTANCE"
+          }
+        ],
+        // Group dotted columns (address.Street, address.City, ...) into nested objects
+        "nested": [
+          {
+            "path": "address",
+            "instanceType": "Address",
+            // Property linking the parent instance to the nested object
+            "property": "has Address",
+            // Use a blank node instead of an ID minted from the parent (optional)
+            "blankNode": false
+          }
+        ],
+        // Columns describing a referenced entity - creates or merges the Manufacturer whose ID
+        // is in "has Manufacturer", reporting values that conflict with ones already loaded
+        "extractEntities": [
+          {
+            "referenceColumn": "has Manufacturer",
+            "instanceType": "Manufacturer",
+            "columns": [
+              { "column": "Manufacturer Name", "mapTo": "label" }
+            ]
+          }
         ]
       },
       {
@@ -175,7 +269,12 @@ pub const FULL_MANIFEST: &str = r#"{
         ],
         "instanceType": "SubClassType",
         // Property that determines the subclass type
-        "subClassProperty": "hasSubClass"
+        "subClassProperty": "hasSubClass",
+        // Resolve a URI column by a property of the already-loaded target instances, rather
+        // than by ID - values that match no instance, or more than one, are reported
+        "lookups": [
+          { "column": "has Manufacturer", "lookupBy": "Manufacturer Name" }
+        ]
       },
       {
         // Properties instance step - processes property values
@@ -214,6 +313,55 @@ pub const FULL_MANIFEST: &str = r#"{
             ]
           }
         ]
+      },
+      {
+        // Example of repeating groups - wide layouts such as Item 1 Material, Item 1 Qty,
+        // Item 2 Material, ... become one linked instance per populated index
+        "path": "wide.csv",
+        "@type": [
+          "CSVImportStep",
+          "BasicInstanceStep"
+        ],
+        "instanceType": "PivotType",
+        "repeatingGroups": [
+          {
+            "instanceType": "PivotItemType",
+            "newRelationshipProperty": "hasItems",
+            // {i} marks where the index appears in each column header
+            "columns": [
+              { "column": "Item {i} Material", "mapTo": "reference" },
+              { "column": "Item {i} Qty", "mapTo": "quantity" }
+            ]
+          }
+        ]
+      },
+      {
+        // Example of an unpivot step - each listed column becomes a property/value pair of the
+        // row's entity, processed the same way as a PropertiesInstanceStep
+        "path": "features-wide.csv",
+        "@type": [
+          "CSVImportStep",
+          "UnpivotInstanceStep"
+        ],
+        "instanceType": "ExampleInstanceType",
+        // Optional - defaults to every column other than the identifier, ignored and extraItems columns
+        "unpivotColumns": ["Color", "Weight"]
+      },
+      {
+        // Example of a relationship step - links existing instances from an association table
+        // with one column for each side of the relationship
+        "path": "product-materials.csv",
+        "@type": [
+          "CSVImportStep",
+          "RelationshipInstanceStep"
+        ],
+        // The type of the instances in the source column
+        "instanceType": "ExampleInstanceType",
+        "sourceColumn": "Product ID",
+        "targetColumn": "Material ID",
+        "relationshipProperty": "hasMaterial",
+        // Also link each target back to its source (optional)
+        "inverseProperty": "usedInProduct"
       }
     ]
   }
@@ -0,0 +1,145 @@
+use super::types::InstanceProcessor;
+use crate::ProcessingState;
+use serde_json::Value as JsonValue;
+use std::mem::take;
+
+impl InstanceProcessor {
+    /// Remember each source and row that contributed values to an instance, for reporting
+    pub(crate) fn record_origin(&mut self, id: &str, row_num: usize) {
+        let origin = (self.current_source.clone(), row_num);
+        let origins = self.instance_origins.entry(id.to_string()).or_default();
+        if !origins.contains(&origin) {
+            origins.push(origin);
+        }
+    }
+
+    /// Report instances with fewer values than a property's min count, or more than its max
+    /// count, for the properties whose domain includes one of the instance's classes
+    pub fn check_cardinality(&mut self) -> ProcessingState {
+        let Some(vocabulary) = self.vocabulary.as_ref() else {
+            return take(&mut self.processing_state);
+        };
+        let constrained: Vec<_> = vocabulary
+            .properties
+            .values()
+            .filter(|property| property.min_count.is_some() || property.max_count.is_some())
+            .filter_map(|property| property.label.clone().map(|label| (label, property)))
+            .collect();
+
+        let mut messages = vec![];
+        let mut ids: Vec<&String> = self.instances.keys().collect();
+        ids.sort();
+        for id in ids {
+            let instance = &self.instances[id];
+            for (label, property) in &constrained {
+                let in_domain = property.domain.iter().flatten().any(|class| {
+                    instance
+                        .type_
+                        .iter()
+                        .any(|t| self.is_instance_of(&t.to_string(), class))
+                });
+                if !in_domain {
+                    continue;
+                }
+                let count = match instance.properties.get(label) {
+                    Some(JsonValue::Array(values)) => values.len(),
+                    Some(_) => 1,
+                    None => 0,
+                };
+                let expected = match (property.min_count, property.max_count) {
+                    (Some(min), _) if count < min => format!("at least {}", min),
+                    (_, Some(max)) if count > max => format!("at most {}", max),
+                    _ => continue,
+                };
+                // Every row the instance was loaded from is listed, as any of them may have
+                // contributed the values being counted
+                let origin = match self.instance_origins.get(id) {
+                    Some(origins) if !origins.is_empty() => format!(
+                        "[{}], ",
+                        origins
+                            .iter()
+                            .map(|(source, row_num)| {
+                                format!("Source: {}, Row: {}", source, row_num + 1)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("; ")
+                    ),
+                    _ => String::new(),
+                };
+                messages.push(format!(
+                    "{}Instance '{}' has {} value(s) for '{}', expected {}",
+                    origin, id, count, label, expected
+                ));
+            }
+        }
+
+        for message in messages {
+            if self.is_strict {
+                self.processing_state
+                    .add_error(message, Some("cardinality".to_string()));
+            } else {
+                self.processing_state
+                    .add_warning(message, Some("cardinality".to_string()));
+            }
+        }
+
+        take(&mut self.processing_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_cardinality() {
+        let manifest = TestManifest::new()
+            .instance_step(
+                "parts.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Part" }),
+            )
+            .build();
+        let model = model_csv(
+            &["Required", "Min Count", "Max Count"],
+            "\
+Part,Part ID,Part,A part,Part ID,Identifier,@id,,,,
+Part,Part Name,Part,A part,Part Name,Display name,String,,true,,
+Part,Supplier,Part,A part,Supplier,Who supplies it,String,,,,1
+Part,Tag,Part,A part,Tag,Search tags,String,,,2,
+",
+        );
+        let parts = "\
+Part ID,Part Name,Supplier,Tag
+P1,Bolt,Acme,steel
+P1,,,metric
+P2,,Acme,a
+P2,,Globex,b
+";
+        let files = [("model.csv", model.as_str()), ("parts.csv", parts)];
+
+        let run = run_import(&manifest, &files, true).await;
+        // Required
+        assert!(run.has_error(
+            "[Source: parts, Row: 3; Source: parts, Row: 4], Instance 'P2' has 0 value(s) for 'Part Name', expected at least 1"
+        ));
+        // Max Count, reported with every row that contributed to the instance
+        assert!(run.has_error(
+            "[Source: parts, Row: 3; Source: parts, Row: 4], Instance 'P2' has 2 value(s) for 'Supplier', expected at most 1"
+        ));
+        assert!(!run.has_error("Instance 'P1'"));
+
+        let parts = "Part ID,Part Name,Tag\nP3,Nut,steel\n";
+        let run = run_import(
+            &manifest,
+            &[("model.csv", model.as_str()), ("parts.csv", parts)],
+            false,
+        )
+        .await;
+        // Min Count
+        assert!(run.has_warning(
+            "[Source: parts, Row: 1], Instance 'P3' has 1 value(s) for 'Tag', expected at least 2"
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+use super::types::InstanceProcessor;
+use crate::error::ProcessorError;
+use crate::types::{PropertyDatatype, ValueConstraints};
+use crate::utils::compare_decimals;
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use std::cmp::Ordering;
+
+impl InstanceProcessor {
+    /// Check a value against its property's pattern, value range and length constraints. The
+    /// pattern and lengths apply to the value as written, the range to the processed number
+    pub(crate) fn check_constraints(
+        &mut self,
+        value: &str,
+        processed_value: &JsonValue,
+        datatype: &PropertyDatatype,
+        constraints: &ValueConstraints,
+        header_name: &str,
+        row_num: usize,
+    ) -> Result<(), ProcessorError> {
+        if constraints.is_empty() {
+            return Ok(());
+        }
+        let value = value.trim();
+        let mut violations = vec![];
+
+        if let Some(pattern) = &constraints.pattern {
+            if let Some(regex) = self.compiled_pattern(pattern) {
+                if !regex.is_match(value) {
+                    violations.push(format!("pattern constraint '{}'", pattern));
+                }
+            }
+        }
+
+        let length = value.chars().count();
+        if let Some(min_length) = constraints.min_length {
+            if length < min_length {
+                violations.push(format!("min length constraint {}", min_length));
+            }
+        }
+        if let Some(max_length) = constraints.max_length {
+            if length > max_length {
+                violations.push(format!("max length constraint {}", max_length));
+            }
+        }
+
+        // Values that failed to parse as numbers have already been reported
+        if let Some(number) = numeric_value(processed_value, datatype) {
+            if let Some(min_value) = &constraints.min_value {
+                if compare_decimals(&number, min_value).is_some_and(Ordering::is_lt) {
+                    violations.push(format!("min value constraint {}", min_value));
+                }
+            }
+            if let Some(max_value) = &constraints.max_value {
+                if compare_decimals(&number, max_value).is_some_and(Ordering::is_gt) {
+                    violations.push(format!("max value constraint {}", max_value));
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+        let msg = format!(
+            "[Column: {}, Row: {}], Value '{}' violates {}",
+            header_name,
+            row_num + 1,
+            value,
+            violations.join(" and ")
+        );
+        if self.is_strict {
+            return Err(ProcessorError::Processing(msg));
+        }
+        self.processing_state
+            .add_warning(msg, Some("value_constraints".to_string()));
+        Ok(())
+    }
+
+    /// A property pattern compiled to match whole values, cached across rows
+    fn compiled_pattern(&mut self, pattern: &str) -> Option<&Regex> {
+        if !self.compiled_patterns.contains_key(pattern) {
+            let regex = Regex::new(&format!("^(?:{})$", pattern)).ok()?;
+            self.compiled_patterns.insert(pattern.to_string(), regex);
+        }
+        self.compiled_patterns.get(pattern)
+    }
+}
+
+/// The decimal digits of a processed numeric value: an integer, an exact decimal string, or the
+/// numeric value of a quantity. Other datatypes have no range
+fn numeric_value(value: &JsonValue, datatype: &PropertyDatatype) -> Option<String> {
+    match value {
+        JsonValue::Number(number)
+            if matches!(
+                datatype,
+                PropertyDatatype::Integer | PropertyDatatype::Long | PropertyDatatype::Decimal
+            ) =>
+        {
+            Some(number.to_string())
+        }
+        JsonValue::String(decimal) if matches!(datatype, PropertyDatatype::Decimal) => {
+            Some(decimal.clone())
+        }
+        JsonValue::Object(quantity) => quantity
+            .get("qudt:numericValue")
+            .and_then(|numeric_value| numeric_value["@value"].as_str())
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::numeric_value;
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use crate::types::PropertyDatatype;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_value_ranges_compare_exactly() {
+        let model = model_csv(
+            &["Unit", "Min Value", "Max Value"],
+            "\
+Reading,Reading ID,Reading,A reading,Reading ID,Identifier,@id,,,,
+Reading,Level,Reading,A reading,Level,Fill level,Decimal,,,,0.3
+Reading,Count,Reading,A reading,Count,Sample count,Integer,,,1,
+Reading,Weight,Reading,A reading,Weight,Sample weight,Decimal,,kg,,10
+",
+        );
+        let manifest = TestManifest::new()
+            .instance_step(
+                "readings.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Reading" }),
+            )
+            .build();
+        let readings = "\
+Reading ID,Level,Count,Weight
+R1,0.30000000000000001,0,10.000000000000001 kg
+R2,0.300,1,10 kg
+";
+        let run = run_import(
+            &manifest,
+            &[("model.csv", &model), ("readings.csv", readings)],
+            true,
+        )
+        .await;
+
+        assert!(run.has_error(
+            "[Column: Level, Row: 1], Value '0.30000000000000001' violates max value constraint 0.3"
+        ));
+        assert!(run.has_error("[Column: Count, Row: 1], Value '0' violates min value constraint 1"));
+        assert!(run.has_error(
+            "[Column: Weight, Row: 1], Value '10.000000000000001 kg' violates max value constraint 10"
+        ));
+        assert!(!run.has_error("Row: 2]"), "{:?}", run.errors);
+    }
+
+    #[test]
+    fn test_only_numeric_values_have_a_range() {
+        assert_eq!(
+            numeric_value(&json!("0.5"), &PropertyDatatype::Decimal).as_deref(),
+            Some("0.5")
+        );
+        assert_eq!(
+            numeric_value(&json!(7), &PropertyDatatype::Long).as_deref(),
+            Some("7")
+        );
+        assert!(numeric_value(&json!("2024"), &PropertyDatatype::GYear).is_none());
+        assert!(numeric_value(&json!("inf"), &PropertyDatatype::String).is_none());
+        // Invalid values kept as strings are not numbers
+        assert!(numeric_value(
+            &json!({ "@value": "NaN", "@type": "xsd:string" }),
+            &PropertyDatatype::Decimal
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,322 @@
+use super::processor_impl::{append_property_values, split_values};
+use super::types::InstanceProcessor;
+use crate::error::ProcessorError;
+use crate::manifest::ImportStep;
+use crate::types::{
+    ExtractedEntity, Header, IdOpt, JsonLdInstance, PropertyDatatype, ValueConstraints,
+};
+use csv::StringRecord;
+use serde_json::{Map, Value as JsonValue};
+
+/// A CSV column holding a property of one of the step's extracted entities
+#[derive(Debug, Clone)]
+pub(crate) struct ExtractedColumn {
+    pub entity_index: usize,
+    pub header: Header,
+}
+
+/// Whether a header is declared as a column of one of the step's extracted entities
+pub(crate) fn is_extracted_column(entities: &[ExtractedEntity], header: &str) -> bool {
+    entities
+        .iter()
+        .any(|entity| entity.columns.iter().any(|column| column.column == header))
+}
+
+/// The values of a property as a list, whether stored as a single value or an array
+fn property_values(value: &JsonValue) -> Vec<&JsonValue> {
+    match value {
+        JsonValue::Array(values) => values.iter().collect(),
+        value => vec![value],
+    }
+}
+
+impl InstanceProcessor {
+    /// Locate the reference column of each extracted entity and match its columns to properties of
+    /// the entity's class
+    pub(crate) fn validate_extracted_headers(
+        &mut self,
+        headers: &[String],
+        step: &ImportStep,
+    ) -> Result<(Vec<usize>, Vec<Option<ExtractedColumn>>), ProcessorError> {
+        let mut reference_indices = Vec::with_capacity(step.extract_entities.len());
+        let mut columns = vec![None; headers.len()];
+        for (entity_index, entity) in step.extract_entities.iter().enumerate() {
+            let reference_index = headers
+                .iter()
+                .position(|h| h == &entity.reference_column)
+                .ok_or_else(|| {
+                    ProcessorError::Processing(format!(
+                        "Reference column '{}' for extracted {} not found in headers: {:?}",
+                        entity.reference_column, entity.instance_type, headers
+                    ))
+                })?;
+            reference_indices.push(reference_index);
+
+            let valid_labels = self.get_valid_property_labels(&entity.instance_type, None, None)?;
+            for column in &entity.columns {
+                let Some(index) = headers.iter().position(|h| h == &column.column) else {
+                    let message = format!(
+                        "Column '{}' for extracted {} not found in headers",
+                        column.column, entity.instance_type
+                    );
+                    if self.is_strict {
+                        return Err(ProcessorError::Processing(message));
+                    }
+                    self.processing_state
+                        .add_warning(message, Some("header_validation".to_string()));
+                    continue;
+                };
+
+                let header = if column.map_to == "label" {
+                    Some(Header {
+                        name: "label".to_string(),
+                        datatype: PropertyDatatype::String,
+                        is_label_header: true,
+                        unit: None,
+                        language: None,
+                        constraints: ValueConstraints::default(),
+                    })
+                } else {
+                    self.find_property(&column.map_to)
+                        .and_then(|p| p.label.clone())
+                        .and_then(|property_label| {
+                            valid_labels
+                                .iter()
+                                .find(|label| label.name == property_label)
+                                .cloned()
+                        })
+                };
+                match header {
+                    Some(header) => {
+                        columns[index] = Some(ExtractedColumn {
+                            entity_index,
+                            header,
+                        })
+                    }
+                    None => {
+                        let message = format!(
+                            "Column '{}' maps to '{}', which is not a property of class '{}'",
+                            column.column, column.map_to, entity.instance_type
+                        );
+                        if self.is_strict {
+                            return Err(ProcessorError::Processing(message));
+                        }
+                        self.processing_state
+                            .add_warning(message, Some("header_validation".to_string()));
+                    }
+                }
+            }
+        }
+
+        Ok((reference_indices, columns))
+    }
+
+    /// Create or merge the entities referenced from a row with the values of their columns
+    pub(crate) fn extract_entities(
+        &mut self,
+        step: &ImportStep,
+        reference_indices: &[usize],
+        columns: &[Option<ExtractedColumn>],
+        headers: &[String],
+        record: &StringRecord,
+        row_num: usize,
+    ) {
+        let mut entities = vec![Map::new(); step.extract_entities.len()];
+        for (i, column) in columns.iter().enumerate() {
+            let Some(column) = column else {
+                continue;
+            };
+            let Some(value) = record.get(i).filter(|value| !value.trim().is_empty()) else {
+                continue;
+            };
+            let mut values = vec![];
+            for value in split_values(step, &column.header.datatype, value) {
+                match self.process_value(value, &column.header, &headers[i], &headers[i], row_num) {
+                    Ok(value) => values.push(value),
+                    Err(e) => {
+                        if self.is_strict {
+                            self.processing_state.add_error_from(e);
+                        } else {
+                            self.processing_state.add_warning_from(e);
+                        }
+                    }
+                }
+            }
+            if !values.is_empty() {
+                append_property_values(
+                    &mut entities[column.entity_index],
+                    &column.header.name,
+                    values,
+                );
+            }
+        }
+
+        for (entity_index, properties) in entities.into_iter().enumerate() {
+            let entity = &step.extract_entities[entity_index];
+            let Some(reference) = record
+                .get(reference_indices[entity_index])
+                .map(str::trim)
+                .filter(|reference| !reference.is_empty())
+            else {
+                continue;
+            };
+            if properties.is_empty() {
+                continue;
+            }
+
+            let id = self.namespaced_id(&entity.instance_type, reference);
+            let properties = self.drop_conflicting_values(&id, properties, row_num);
+            let instance = JsonLdInstance {
+                id: IdOpt::String(id),
+                type_: vec![IdOpt::String(entity.instance_type.clone())],
+                properties,
+            };
+            if let Err(e) = self.update_or_insert_instance(instance) {
+                if self.is_strict {
+                    self.processing_state.add_error_from(e);
+                } else {
+                    self.processing_state.add_warning_from(e);
+                }
+            }
+        }
+    }
+
+    /// Report and remove extracted values that disagree with the values already loaded for the
+    /// instance, keeping the loaded values
+    fn drop_conflicting_values(
+        &mut self,
+        id: &str,
+        mut properties: Map<String, JsonValue>,
+        row_num: usize,
+    ) -> Map<String, JsonValue> {
+        let Some(existing) = self.instances.get(id) else {
+            return properties;
+        };
+
+        let mut conflicts = vec![];
+        for (key, value) in &properties {
+            let Some(loaded) = existing.properties.get(key) else {
+                continue;
+            };
+            let loaded_values = property_values(loaded);
+            if property_values(value)
+                .iter()
+                .any(|value| !loaded_values.contains(value))
+            {
+                conflicts.push((
+                    key.clone(),
+                    format!(
+                    "Conflicting value for '{}' on instance '{}' at row {}: loaded {}, extracted {}",
+                    key,
+                    id,
+                    row_num + 1,
+                    loaded,
+                    value
+                    ),
+                ));
+            }
+        }
+
+        for (key, message) in conflicts {
+            properties.remove(&key);
+            let error = ProcessorError::Processing(message);
+            if self.is_strict {
+                self.processing_state.add_error_from(error);
+            } else {
+                self.processing_state.add_warning_from(error);
+            }
+        }
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, ImportRun, TestManifest};
+    use serde_json::json;
+
+    const MATERIALS: &str = "\
+Material ID,has Manufacturer,Manufacturer Name,Manufacturer Country
+M1,MF1,Acme,US
+M2,MF2,Globex,DE
+M3,MF1,Acme,FR
+";
+
+    async fn import_materials(is_strict: bool) -> ImportRun {
+        let manifest = TestManifest::new()
+            .instance_step(
+                "manufacturers.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Manufacturer" }),
+            )
+            .instance_step(
+                "materials.csv",
+                "BasicInstanceStep",
+                json!({
+                    "instanceType": "Material",
+                    "extractEntities": [{
+                        "referenceColumn": "has Manufacturer",
+                        "instanceType": "Manufacturer",
+                        "columns": [
+                            { "column": "Manufacturer Name", "mapTo": "label" },
+                            { "column": "Manufacturer Country", "mapTo": "Country" }
+                        ]
+                    }]
+                }),
+            );
+        let model = model_csv(
+            &[],
+            "\
+Manufacturer,Manufacturer ID,Manufacturer,A manufacturer,Manufacturer ID,Identifier,@id,
+Manufacturer,Country,Manufacturer,A manufacturer,Country,Country of origin,String,
+Material,Material ID,Material,A material,Material ID,Identifier,@id,
+Material,hasManufacturer,Material,A material,has Manufacturer,Who makes it,URI,Manufacturer
+",
+        );
+        run_import(
+            &manifest.build(),
+            &[
+                ("model.csv", &model),
+                ("manufacturers.csv", "Manufacturer ID,Country\nMF1,US\n"),
+                ("materials.csv", MATERIALS),
+            ],
+            is_strict,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_extracted_entities_are_created_and_merged() {
+        let run = import_materials(false).await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let acme = run.instance("MF1").unwrap();
+        assert_eq!(acme["label"], json!(["Acme"]));
+        assert_eq!(acme["Country"], json!(["US"]));
+        let globex = run.instance("MF2").unwrap();
+        assert_eq!(globex["@type"], json!(["Manufacturer"]));
+        assert_eq!(globex["label"], json!(["Globex"]));
+        assert_eq!(globex["Country"], json!(["DE"]));
+        assert_eq!(
+            run.instance("M2").unwrap()["has Manufacturer"],
+            json!(["MF2"])
+        );
+        // The extraction columns are not properties of the material
+        assert!(run
+            .instance("M1")
+            .unwrap()
+            .get("Manufacturer Name")
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_extracted_conflicts_are_reported() {
+        let run = import_materials(true).await;
+        assert!(run.has_error("Conflicting value for 'Country' on instance 'MF1' at row 3"));
+
+        let run = import_materials(false).await;
+        assert!(run.has_warning("Conflicting value for 'Country' on instance 'MF1' at row 3"));
+        assert_eq!(run.instance("MF1").unwrap()["Country"], json!(["US"]));
+    }
+}
@@ -0,0 +1,250 @@
+use super::types::InstanceProcessor;
+use crate::error::ProcessorError;
+use crate::types::PivotColumn;
+use crate::utils::{render_id_template, template_placeholders, to_kebab_case};
+use csv::StringRecord;
+use uuid::Uuid;
+
+impl InstanceProcessor {
+    /// Prefix an instance identifier with its class namespace when `namespaceIris` is enabled
+    pub(crate) fn namespaced_id(&self, class_type: &str, id: &str) -> String {
+        if self.is_namespace_iris {
+            format!("{}/{}", to_kebab_case(class_type), id)
+        } else {
+            id.to_string()
+        }
+    }
+
+    /// Ensure every column referenced by an idTemplate is present in the CSV headers
+    pub(crate) fn validate_id_template(
+        &self,
+        id_template: &str,
+        headers: &[String],
+    ) -> Result<(), ProcessorError> {
+        let missing_columns: Vec<String> = template_placeholders(id_template)?
+            .into_iter()
+            .filter(|column| !headers.contains(column))
+            .collect();
+        if !missing_columns.is_empty() {
+            return Err(ProcessorError::Processing(format!(
+                "idTemplate '{}' references columns not found in headers: {:?}",
+                id_template, missing_columns
+            )));
+        }
+        Ok(())
+    }
+
+    /// Resolve the (un-namespaced) identifier of a row, either from the identifier column or by
+    /// rendering the step's idTemplate against the row
+    pub(crate) fn row_identifier(
+        &self,
+        id_template: Option<&str>,
+        id_column_index: Option<usize>,
+        headers: &[String],
+        record: &StringRecord,
+        row_num: usize,
+    ) -> Result<String, ProcessorError> {
+        if let Some(id_template) = id_template {
+            return render_id_template(id_template, |column| {
+                headers
+                    .iter()
+                    .position(|h| h == column)
+                    .and_then(|i| record.get(i))
+            })
+            .map_err(|e| {
+                ProcessorError::Processing(format!(
+                    "Failed to build identifier at row {}: {}",
+                    row_num + 1,
+                    e
+                ))
+            });
+        }
+
+        match id_column_index.and_then(|i| record.get(i)) {
+            Some(id) if !id.is_empty() => Ok(id.to_string()),
+            _ => Err(ProcessorError::Processing(format!(
+                "Missing or empty identifier value at row {}",
+                row_num + 1
+            ))),
+        }
+    }
+
+    /// Mint the identifier of the instance created from a pivot column group. Without an
+    /// idTemplate, a UUIDv5 is derived from the parent ID, the pivot group and the row's pivot
+    /// column values, so repeated imports of the same data produce the same IRIs
+    pub(crate) fn pivot_identifier(
+        &self,
+        pivot_column: &PivotColumn,
+        parent_id: &str,
+        headers: &[String],
+        record: &StringRecord,
+        row_num: usize,
+    ) -> Result<String, ProcessorError> {
+        if let Some(id_template) = &pivot_column.id_template {
+            let id = self.row_identifier(Some(id_template), None, headers, record, row_num)?;
+            return Ok(self.namespaced_id(&pivot_column.instance_type, &id));
+        }
+
+        let mut name = vec![
+            self.instances_base_iri.clone(),
+            parent_id.to_string(),
+            pivot_column.new_relationship_property.clone(),
+            pivot_column.instance_type.clone(),
+        ];
+        for column in &pivot_column.columns {
+            let value = headers
+                .iter()
+                .position(|h| h == column)
+                .and_then(|i| record.get(i))
+                .unwrap_or_default()
+                .trim();
+            name.push(format!("{}={}", column, value));
+        }
+        Ok(Uuid::new_v5(&Uuid::NAMESPACE_URL, name.join("\u{1f}").as_bytes()).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, ImportRun, TestManifest};
+    use serde_json::json;
+
+    fn stock_model() -> String {
+        model_csv(
+            &[],
+            "\
+Stock,Warehouse,Stock,Stock level,Warehouse,Warehouse name,String,
+Stock,Item,Stock,Stock level,Item,Item number,String,
+Stock,Quantity,Stock,Stock level,Quantity,Units on hand,Integer,
+",
+        )
+    }
+
+    #[tokio::test]
+    async fn test_id_template_with_namespace_iris() {
+        let manifest = TestManifest::new()
+            .with_instances("baseIRI", json!("http://example.com/ids/"))
+            .with_instances("namespaceIris", json!(true))
+            .instance_step(
+                "stock.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Stock", "idTemplate": "{Warehouse}/{Item}" }),
+            );
+        let stock = "Warehouse,Item,Quantity\nCarmel North,135,4\nCarmel North,136,\n";
+        let run = run_import(
+            &manifest.build(),
+            &[("model.csv", &stock_model()), ("stock.csv", stock)],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let stock = run.instance("stock/Carmel%20North/135").unwrap();
+        assert_eq!(stock["Quantity"], serde_json::json!([4]));
+        assert!(run.instance("stock/Carmel%20North/136").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_id_template_with_missing_column() {
+        let manifest = TestManifest::new().instance_step(
+            "stock.csv",
+            "BasicInstanceStep",
+            json!({ "instanceType": "Stock", "idTemplate": "{Warehouse}/{Bin}" }),
+        );
+        let stock = "Warehouse,Item,Quantity\nCarmel,135,4\n";
+        let run = run_import(
+            &manifest.build(),
+            &[("model.csv", &stock_model()), ("stock.csv", stock)],
+            true,
+        )
+        .await;
+
+        assert!(run.has_error("references columns not found in headers"));
+    }
+
+    #[tokio::test]
+    async fn test_pivot_identifiers_are_stable() {
+        let model = model_csv(
+            &[],
+            "\
+Supplier,Supplier ID,Supplier,A supplier,Supplier ID,Identifier,@id,
+Supplier,Address,Supplier,A supplier,Address,Supplier address,URI,Address
+Address,Street,Address,An address,Street,Street line,String,
+Address,City,Address,An address,City,City name,String,
+",
+        );
+        let manifest = TestManifest::new()
+            .with_instances("baseIRI", json!("http://example.com/ids/"))
+            .instance_step(
+                "suppliers.csv",
+                "BasicInstanceStep",
+                json!({
+                    "instanceType": "Supplier",
+                    "pivotColumns": [{
+                        "instanceType": "Address",
+                        "newRelationshipProperty": "Address",
+                        "columns": ["Street", "City"]
+                    }]
+                }),
+            )
+            .build();
+        let suppliers = "Supplier ID,Street,City\nS1,1 Main St,Carmel\nS2,1 Main St,Carmel\n";
+        let files = [("model.csv", model.as_str()), ("suppliers.csv", suppliers)];
+        let first = run_import(&manifest, &files, false).await;
+        let second = run_import(&manifest, &files, false).await;
+
+        let address_id = |run: &ImportRun, supplier: &str| {
+            run.instance(supplier).unwrap()["Address"]["@id"].clone()
+        };
+        assert!(first.errors.is_empty(), "{:?}", first.errors);
+        assert_eq!(address_id(&first, "S1"), address_id(&second, "S1"));
+        // The parent ID is part of the name, so equal addresses of two suppliers stay distinct
+        assert_ne!(address_id(&first, "S1"), address_id(&first, "S2"));
+        let address = first
+            .instance(address_id(&first, "S1").as_str().unwrap())
+            .unwrap();
+        assert_eq!(address["City"], serde_json::json!(["Carmel"]));
+    }
+
+    #[tokio::test]
+    async fn test_empty_pivot_groups_mint_no_identifier() {
+        let model = model_csv(
+            &[],
+            "\
+Supplier,Supplier ID,Supplier,A supplier,Supplier ID,Identifier,@id,
+Supplier,Address,Supplier,A supplier,Address,Supplier address,URI,Address
+Address,Address Code,Address,An address,Address Code,Address code,String,
+Address,City,Address,An address,City,City name,String,
+",
+        );
+        let manifest = TestManifest::new()
+            .instance_step(
+                "suppliers.csv",
+                "BasicInstanceStep",
+                json!({
+                    "instanceType": "Supplier",
+                    "pivotColumns": [{
+                        "instanceType": "Address",
+                        "newRelationshipProperty": "Address",
+                        "columns": ["Address Code", "City"],
+                        "idTemplate": "address-{Address Code}"
+                    }]
+                }),
+            )
+            .build();
+        let suppliers = "Supplier ID,Address Code,City\nS1,A1,Carmel\nS2,,\n";
+        let run = run_import(
+            &manifest,
+            &[("model.csv", &model), ("suppliers.csv", suppliers)],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert_eq!(
+            run.instance("address-A1").unwrap()["City"],
+            json!(["Carmel"])
+        );
+        assert!(run.instance("S2").unwrap().get("Address").is_none());
+    }
+}
@@ -0,0 +1,162 @@
+use super::types::InstanceProcessor;
+use crate::ProcessingState;
+use std::mem::take;
+
+/// A reference to another instance made by a URI or Picklist value, kept so it can be resolved
+/// once every instance step has run
+#[derive(Debug, Clone)]
+pub(crate) struct InstanceReference {
+    pub source: String,
+    pub row_num: usize,
+    pub column: String,
+    pub target: String,
+}
+
+impl InstanceProcessor {
+    pub(crate) fn record_reference(&mut self, column: &str, row_num: usize, target: &str) {
+        self.references.push(InstanceReference {
+            source: self.current_source.clone(),
+            row_num,
+            column: column.to_string(),
+            target: target.to_string(),
+        });
+    }
+
+    /// Report references to instances that were never loaded
+    pub fn check_references(&mut self) -> ProcessingState {
+        for reference in take(&mut self.references) {
+            if self.instances.contains_key(&reference.target) {
+                continue;
+            }
+            let message = format!(
+                "[Source: {}, Column: {}, Row: {}], Reference to '{}' does not match any loaded instance",
+                reference.source,
+                reference.column,
+                reference.row_num + 1,
+                reference.target
+            );
+            if self.is_strict {
+                self.processing_state
+                    .add_error(message, Some("referential_integrity".to_string()));
+            } else {
+                self.processing_state
+                    .add_warning(message, Some("referential_integrity".to_string()));
+            }
+        }
+
+        take(&mut self.processing_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::json;
+
+    fn model() -> String {
+        model_csv(
+            &[],
+            "\
+Part,Part ID,Part,A part,Part ID,Identifier,@id,
+Part,Color,Part,A part,Color,Color,Picklist,Color
+Part,Made By,Part,A part,Made By,Manufacturer part,URI,Part
+Color,Color Name,Color,A color,Color Name,Name,@id,
+",
+        )
+    }
+
+    fn manifest() -> String {
+        TestManifest::new()
+            .with_instances("baseIRI", json!("http://example.com/data/"))
+            .with_instances("namespaceIris", json!(true))
+            .instance_step(
+                "colors.csv",
+                "PicklistStep",
+                json!({ "instanceType": "Color" }),
+            )
+            .instance_step(
+                "parts.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Part" }),
+            )
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_loaded_references_are_not_dangling() {
+        let parts = "Part ID,Color,Made By\nP1,Red,\nP2,Blue,P1\n";
+        let run = run_import(
+            &manifest(),
+            &[
+                ("model.csv", &model()),
+                ("colors.csv", "Color Name\nRed\nBlue\n"),
+                ("parts.csv", parts),
+            ],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert_eq!(
+            run.instance("part/P2").unwrap()["Made By"],
+            json!(["part/P1"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dangling_references_are_reported() {
+        let parts = "Part ID,Color,Made By\nP1,Red,P9\n";
+        let model = model();
+        let files = [
+            ("model.csv", model.as_str()),
+            ("colors.csv", "Color Name\nRed\nBlue\n"),
+            ("parts.csv", parts),
+        ];
+
+        let run = run_import(&manifest(), &files, true).await;
+        assert!(run.has_error("[Source: parts, Column: Made By, Row: 1], Reference to 'part/P9'"));
+
+        let run = run_import(&manifest(), &files, false).await;
+        assert!(run.has_warning("Reference to 'part/P9' does not match any loaded instance"));
+        assert!(!run.has_warning("Reference to 'color/Red'"));
+    }
+
+    const ONTOLOGY: &str = r#"
+        @prefix ex: <http://example.com/terms/> .
+        @prefix owl: <http://www.w3.org/2002/07/owl#> .
+        @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+
+        ex:Part a owl:Class .
+        ex:Color a owl:Class ;
+            owl:oneOf ( <http://example.com/data/color/Red> <http://example.com/data/color/Blue> ) .
+        ex:color a owl:ObjectProperty ; rdfs:label "Color" ; rdfs:domain ex:Part ;
+            rdfs:range ex:Color .
+    "#;
+
+    #[tokio::test]
+    async fn test_ontology_picklist_values_are_not_dangling_references() {
+        let manifest = TestManifest::new()
+            .with_model("baseOntology", json!("ontology.ttl"))
+            .with_instances("baseIRI", json!("http://example.com/data/"))
+            .instance_step(
+                "parts.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Part" }),
+            );
+        let model = model_csv(&[], "Part,Part ID,Part,A part,Part ID,Identifier,@id,\n");
+        let run = run_import(
+            &manifest.build(),
+            &[
+                ("ontology.ttl", ONTOLOGY),
+                ("model.csv", &model),
+                ("parts.csv", "Part ID,Color\nP1,Red\n"),
+            ],
+            true,
+        )
+        .await;
+
+        // No Color instances are loaded, but the ontology's oneOf values are valid references
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert_eq!(run.instance("P1").unwrap()["Color"], json!(["Red"]));
+    }
+}
@@ -0,0 +1,190 @@
+use super::types::InstanceProcessor;
+use crate::error::ProcessorError;
+use crate::manifest::ImportStep;
+use crate::types::{Header, PropertyDatatype};
+use crate::utils::{expand_iri_with_base, to_pascal_case};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// Index of already-loaded instances of a column's target class, keyed by the value of the
+/// lookup property
+#[derive(Debug, Clone)]
+pub(crate) struct LookupIndex {
+    pub lookup_by: String,
+    pub target_class: String,
+    pub ids: HashMap<String, Vec<String>>,
+}
+
+impl InstanceProcessor {
+    /// Build an index for each column declared in the step's lookups
+    pub(crate) fn build_lookup_indexes(
+        &mut self,
+        step: &ImportStep,
+        headers: &[Option<Header>],
+        csv_headers: &[String],
+    ) -> Result<Vec<Option<LookupIndex>>, ProcessorError> {
+        let mut indexes = vec![None; csv_headers.len()];
+        for lookup in &step.lookups {
+            let index = csv_headers
+                .iter()
+                .position(|h| h == &lookup.column)
+                .ok_or_else(|| {
+                    ProcessorError::Processing(format!(
+                        "Lookup column '{}' not found in headers: {:?}",
+                        lookup.column, csv_headers
+                    ))
+                })?;
+
+            let target_class = match headers[index].as_ref().map(|h| &h.datatype) {
+                Some(PropertyDatatype::URI(Some(target_class))) => target_class.clone(),
+                _ => {
+                    return Err(ProcessorError::Processing(format!(
+                        "Lookup column '{}' must be a property with a URI range of a class",
+                        lookup.column
+                    )))
+                }
+            };
+
+            let lookup_by = if lookup.lookup_by == "label" {
+                lookup.lookup_by.clone()
+            } else {
+                self.find_property(&lookup.lookup_by)
+                    .and_then(|p| p.label.clone())
+                    .ok_or_else(|| {
+                        ProcessorError::Processing(format!(
+                            "lookupBy property '{}' for column '{}' not found in vocabulary",
+                            lookup.lookup_by, lookup.column
+                        ))
+                    })?
+            };
+
+            let mut ids: HashMap<String, Vec<String>> = HashMap::new();
+            for (id, instance) in &self.instances {
+                if !instance
+                    .type_
+                    .iter()
+                    .any(|t| self.is_instance_of(&t.to_string(), &target_class))
+                {
+                    continue;
+                }
+                let values = match instance.properties.get(&lookup_by) {
+                    Some(JsonValue::Array(values)) => values.iter().collect(),
+                    Some(value) => vec![value],
+                    None => vec![],
+                };
+                for value in values {
+                    let key = match value {
+                        JsonValue::String(value) => value.trim().to_string(),
+                        value => value.to_string(),
+                    };
+                    let entry = ids.entry(key).or_default();
+                    if !entry.contains(id) {
+                        entry.push(id.clone());
+                    }
+                }
+            }
+
+            indexes[index] = Some(LookupIndex {
+                lookup_by,
+                target_class,
+                ids,
+            });
+        }
+
+        Ok(indexes)
+    }
+
+    /// Whether an instance type names the given class
+    pub(crate) fn is_instance_of(&self, instance_type: &str, target_class: &str) -> bool {
+        instance_type == target_class
+            || expand_iri_with_base(&self.model_base_iri, &to_pascal_case(instance_type))
+                == target_class
+    }
+
+    /// Resolve a lookup value to the ID of the single instance it matches
+    pub(crate) fn resolve_lookup(
+        &self,
+        lookup: &LookupIndex,
+        value: &str,
+        header_name: &str,
+        row_num: usize,
+    ) -> Result<String, ProcessorError> {
+        match lookup.ids.get(value.trim()).map(Vec::as_slice) {
+            Some([id]) => Ok(id.clone()),
+            Some(ids) if !ids.is_empty() => {
+                let mut ids = ids.to_vec();
+                ids.sort();
+                Err(ProcessorError::Processing(format!(
+                    "[Column: {}, Row: {}], Ambiguous lookup of '{}' by '{}': matches {:?}",
+                    header_name,
+                    row_num + 1,
+                    value,
+                    lookup.lookup_by,
+                    ids
+                )))
+            }
+            _ => Err(ProcessorError::Processing(format!(
+                "[Column: {}, Row: {}], No {} instance found with '{}' of '{}'",
+                header_name,
+                row_num + 1,
+                lookup.target_class,
+                lookup.lookup_by,
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::json;
+
+    const MODEL: &str = "\
+Supplier,Supplier ID,Supplier,A supplier,Supplier ID,Identifier,@id,
+Supplier,Supplier Name,Supplier,A supplier,Supplier Name,Trading name,String,
+Part,Part ID,Part,A part,Part ID,Identifier,@id,
+Part,Supplier,Part,A part,Supplier,Who supplies it,URI,Supplier
+";
+
+    const SUPPLIERS: &str = "Supplier ID,Supplier Name\nS1,Acme\nS2,Globex\nS3,Acme\n";
+
+    #[tokio::test]
+    async fn test_lookup_resolves_by_property_value() {
+        let parts = "Part ID,Supplier\nP1, Globex \nP2,Acme\nP3,Initech\n";
+        let manifest = TestManifest::new()
+            .instance_step(
+                "suppliers.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Supplier" }),
+            )
+            .instance_step(
+                "parts.csv",
+                "BasicInstanceStep",
+                json!({
+                    "instanceType": "Part",
+                    "lookups": [{ "column": "Supplier", "lookupBy": "Supplier Name" }]
+                }),
+            )
+            .build();
+        let model = model_csv(&[], MODEL);
+        let files = [
+            ("model.csv", model.as_str()),
+            ("suppliers.csv", SUPPLIERS),
+            ("parts.csv", parts),
+        ];
+
+        let run = run_import(&manifest, &files, true).await;
+        assert!(run.has_error(
+            "[Column: Supplier, Row: 2], Ambiguous lookup of 'Acme' by 'Supplier Name': matches [\"S1\", \"S3\"]"
+        ));
+        assert!(run.has_error("[Column: Supplier, Row: 3], No http://example.com/terms/Supplier instance found with 'Supplier Name' of 'Initech'"));
+
+        let run = run_import(&manifest, &files, false).await;
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert!(run.has_warning("Ambiguous lookup of 'Acme'"));
+        assert_eq!(run.instance("P1").unwrap()["Supplier"], json!(["S2"]));
+        assert!(run.instance("P2").unwrap().get("Supplier").is_none());
+        assert!(run.instance("P3").unwrap().get("Supplier").is_none());
+    }
+}
@@ -1,6 +1,16 @@
+mod cardinality;
+mod constraints;
+mod extraction;
+mod identifiers;
+mod integrity;
+mod lookup;
+mod nested;
 mod processor_impl;
+mod relationships;
+mod repeating;
 mod serializer;
 mod types;
+mod units;
 mod validation;
 mod value_processor;
 
@@ -77,6 +87,34 @@ impl InstanceManager {
             .await
     }
 
+    pub async fn process_unpivot_instance(
+        &mut self,
+        step: &ImportStep,
+        s3_client: Option<&aws_sdk_s3::Client>,
+    ) -> Result<ProcessingState, ProcessorError> {
+        self.processor
+            .process_unpivot_instance(step, s3_client)
+            .await
+    }
+
+    pub async fn process_relationship_instance(
+        &mut self,
+        step: &ImportStep,
+        s3_client: Option<&aws_sdk_s3::Client>,
+    ) -> Result<ProcessingState, ProcessorError> {
+        self.processor
+            .process_relationship_instance(step, s3_client)
+            .await
+    }
+
+    pub fn check_references(&mut self) -> ProcessingState {
+        self.processor.check_references()
+    }
+
+    pub fn check_cardinality(&mut self) -> ProcessingState {
+        self.processor.check_cardinality()
+    }
+
     pub async fn save_instances(
         &self,
         output_path: &StorageLocation,
@@ -0,0 +1,330 @@
+use super::processor_impl::{append_property_values, split_values};
+use super::types::InstanceProcessor;
+use crate::error::ProcessorError;
+use crate::manifest::ImportStep;
+use crate::types::{Header, NestedObject};
+use crate::utils::to_kebab_case;
+use csv::StringRecord;
+use serde_json::{Map, Value as JsonValue};
+
+/// A CSV column holding a property of one of the step's nested objects
+#[derive(Debug, Clone)]
+pub(crate) struct NestedColumn {
+    pub nested_index: usize,
+    pub header: Header,
+}
+
+/// The nested object a header belongs to and the property label it refers to, preferring the
+/// most specific declared path
+pub(crate) fn nested_for_header<'a>(
+    nested: &[NestedObject],
+    header: &'a str,
+) -> Option<(usize, &'a str)> {
+    nested
+        .iter()
+        .enumerate()
+        .filter_map(|(i, n)| {
+            n.property_label(header)
+                .map(|label| (i, n.path.len(), label))
+        })
+        .max_by_key(|(_, path_len, _)| *path_len)
+        .map(|(i, _, label)| (i, label))
+}
+
+/// The nested object whose path encloses the nested object at `index`, if any
+fn parent_nested(nested: &[NestedObject], index: usize) -> Option<usize> {
+    let path = &nested[index].path;
+    nested
+        .iter()
+        .enumerate()
+        .filter(|(i, n)| {
+            *i != index
+                && path
+                    .strip_prefix(n.path.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+        .max_by_key(|(_, n)| n.path.len())
+        .map(|(i, _)| i)
+}
+
+impl InstanceProcessor {
+    /// Match the columns of a step's nested objects to properties of each nested object's class,
+    /// and check that each linking property is defined on the enclosing class
+    pub(crate) fn validate_nested_headers(
+        &mut self,
+        headers: &[String],
+        class_type: &str,
+        step: &ImportStep,
+    ) -> Result<Vec<Option<NestedColumn>>, ProcessorError> {
+        if step.nested.is_empty() {
+            return Ok(vec![None; headers.len()]);
+        }
+
+        let mut valid_labels = Vec::with_capacity(step.nested.len());
+        for (i, nested) in step.nested.iter().enumerate() {
+            let parent_type = parent_nested(&step.nested, i)
+                .map(|p| step.nested[p].instance_type.clone())
+                .unwrap_or_else(|| class_type.to_string());
+            let parent_labels = self.get_valid_property_labels(&parent_type, None, None)?;
+            if !parent_labels.iter().any(|l| l.name == nested.property) {
+                let message = format!(
+                    "Nested property '{}' for path '{}' is not a property of class '{}'",
+                    nested.property, nested.path, parent_type
+                );
+                if self.is_strict {
+                    return Err(ProcessorError::Processing(message));
+                } else {
+                    self.processing_state
+                        .add_warning(message, Some("header_validation".to_string()));
+                }
+            }
+            valid_labels.push(self.get_valid_property_labels(&nested.instance_type, None, None)?);
+        }
+
+        let mut unknown_headers = Vec::new();
+        let columns = headers
+            .iter()
+            .map(|header| {
+                let (nested_index, label) = nested_for_header(&step.nested, header)?;
+                match valid_labels[nested_index].iter().find(|l| l.name == label) {
+                    Some(valid_header) => Some(NestedColumn {
+                        nested_index,
+                        header: valid_header.clone(),
+                    }),
+                    None => {
+                        unknown_headers.push(header.clone());
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        if !unknown_headers.is_empty() {
+            let message = format!(
+                "Unknown nested columns found in CSV for class '{}': {:?}. These columns do not correspond to any properties of their nested object's class.",
+                class_type, unknown_headers
+            );
+            if self.is_strict {
+                return Err(ProcessorError::Processing(message));
+            } else {
+                self.processing_state
+                    .add_warning(message, Some("header_validation".to_string()));
+            }
+        }
+
+        Ok(columns)
+    }
+
+    /// Build a row's nested objects, returning the linking property and node object for each
+    /// top-level nested object that has values
+    pub(crate) fn nested_values(
+        &mut self,
+        step: &ImportStep,
+        columns: &[Option<NestedColumn>],
+        headers: &[String],
+        record: &StringRecord,
+        parent_id: &str,
+        row_num: usize,
+    ) -> Vec<(String, JsonValue)> {
+        if step.nested.is_empty() {
+            return vec![];
+        }
+
+        let mut objects = vec![Map::new(); step.nested.len()];
+        for (i, column) in columns.iter().enumerate() {
+            let Some(column) = column else {
+                continue;
+            };
+            let Some(value) = record.get(i).filter(|value| !value.is_empty()) else {
+                continue;
+            };
+            let mut values = vec![];
+            for value in split_values(step, &column.header.datatype, value) {
+                match self.process_value(value, &column.header, &headers[i], &headers[i], row_num) {
+                    Ok(value) => values.push(value),
+                    Err(e) => {
+                        if self.is_strict {
+                            self.processing_state.add_error_from(e);
+                        } else {
+                            self.processing_state.add_warning_from(e);
+                        }
+                    }
+                }
+            }
+            if !values.is_empty() {
+                append_property_values(
+                    &mut objects[column.nested_index],
+                    &column.header.name,
+                    values,
+                );
+            }
+        }
+
+        // Shallow paths first, so minted IDs can build on their enclosing object's ID
+        let mut order: Vec<usize> = (0..step.nested.len()).collect();
+        order.sort_by_key(|&i| step.nested[i].path.matches('.').count());
+
+        // An object is only emitted when it, or an object nested within it, has values
+        let mut has_values = vec![false; step.nested.len()];
+        for i in (0..step.nested.len()).filter(|&i| !objects[i].is_empty()) {
+            let mut current = Some(i);
+            while let Some(index) = current {
+                has_values[index] = true;
+                current = parent_nested(&step.nested, index);
+            }
+        }
+
+        let mut ids: Vec<Option<String>> = vec![None; step.nested.len()];
+        for &i in &order {
+            let nested = &step.nested[i];
+            ids[i] = if !has_values[i] || nested.blank_node {
+                None
+            } else if let Some(id_template) = &nested.id_template {
+                match self.row_identifier(Some(id_template), None, headers, record, row_num) {
+                    Ok(id) => Some(self.namespaced_id(&nested.instance_type, &id)),
+                    Err(e) => {
+                        let message = format!(
+                            "{} for nested path '{}', using a blank node",
+                            e, nested.path
+                        );
+                        if self.is_strict {
+                            self.processing_state
+                                .add_error_from(ProcessorError::Processing(message));
+                        } else {
+                            self.processing_state
+                                .add_warning(message, Some("instance_processing".to_string()));
+                        }
+                        None
+                    }
+                }
+            } else {
+                let parent_id = match parent_nested(&step.nested, i) {
+                    Some(parent) => ids[parent].clone(),
+                    None => Some(parent_id.to_string()),
+                };
+                let segment = nested.path.rsplit('.').next().unwrap_or(&nested.path);
+                parent_id.map(|parent_id| format!("{}/{}", parent_id, to_kebab_case(segment)))
+            };
+        }
+
+        // Deepest paths first, so each object is complete before it is embedded in its parent
+        let mut values = vec![];
+        for &i in order.iter().rev() {
+            if !has_values[i] {
+                continue;
+            }
+            let mut object = std::mem::take(&mut objects[i]);
+            let nested = &step.nested[i];
+            if let Some(id) = &ids[i] {
+                object.insert("@id".to_string(), JsonValue::String(id.clone()));
+            }
+            object.insert(
+                "@type".to_string(),
+                JsonValue::Array(vec![JsonValue::String(nested.instance_type.clone())]),
+            );
+            match parent_nested(&step.nested, i) {
+                Some(parent) => append_property_values(
+                    &mut objects[parent],
+                    &nested.property,
+                    vec![JsonValue::Object(object)],
+                ),
+                None => values.push((nested.property.clone(), JsonValue::Object(object))),
+            }
+        }
+
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::{json, Value as JsonValue};
+
+    fn model() -> String {
+        model_csv(
+            &[],
+            "\
+Customer,Customer ID,Customer,A customer,Customer ID,Identifier,@id,
+Customer,Address,Customer,A customer,Address,Postal address,URI,Address
+Address,Street,Address,A postal address,Street,Street line,String,
+Address,City,Address,A postal address,City,City name,String,
+Address,Location,Address,A postal address,Location,Coordinates,URI,Point
+Point,Latitude,Point,A coordinate,Latitude,Latitude in degrees,Decimal,
+",
+        )
+    }
+
+    /// A manifest importing `customers.csv` with the given `nested` objects
+    fn manifest(nested: JsonValue) -> String {
+        TestManifest::new()
+            .instance_step(
+                "customers.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Customer", "nested": nested }),
+            )
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_nested_objects_from_dotted_headers() {
+        let manifest = manifest(json!([
+            { "path": "address", "instanceType": "Address", "property": "Address" },
+            { "path": "address.geo", "instanceType": "Point", "property": "Location" }
+        ]));
+        let customers = "\
+Customer ID,address.Street,address.City,address.geo.Latitude
+C1,1 Main St,Carmel,39.97
+C2,,Zionsville,
+C3,,,
+";
+        let run = run_import(
+            &manifest,
+            &[("model.csv", &model()), ("customers.csv", customers)],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert_eq!(
+            run.instance("C1").unwrap()["Address"],
+            json!([{
+                "@id": "C1/address",
+                "@type": ["Address"],
+                "Street": ["1 Main St"],
+                "City": ["Carmel"],
+                "Location": [{
+                    "@id": "C1/address/geo",
+                    "@type": ["Point"],
+                    "Latitude": ["39.97"]
+                }]
+            }])
+        );
+        assert_eq!(
+            run.instance("C2").unwrap()["Address"],
+            json!([{ "@id": "C2/address", "@type": ["Address"], "City": ["Zionsville"] }])
+        );
+        // Objects without any values are not emitted
+        assert!(run.instance("C3").unwrap().get("Address").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_nested_blank_nodes_and_unknown_columns() {
+        let manifest = manifest(json!([
+            { "path": "address", "instanceType": "Address", "property": "Address", "blankNode": true }
+        ]));
+        let customers = "Customer ID,address.City,address.Country\nC1,Carmel,US\n";
+        let model = model();
+        let files = [("model.csv", model.as_str()), ("customers.csv", customers)];
+
+        let run = run_import(&manifest, &files, true).await;
+        assert!(run.has_error("Unknown nested columns"));
+
+        let run = run_import(&manifest, &files, false).await;
+        assert!(run.has_warning("address.Country"));
+        assert_eq!(
+            run.instance("C1").unwrap()["Address"],
+            json!([{ "@type": ["Address"], "City": ["Carmel"] }])
+        );
+    }
+}
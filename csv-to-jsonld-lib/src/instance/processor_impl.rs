@@ -1,14 +1,16 @@
+use super::repeating::RepeatingPatterns;
 use super::types::InstanceProcessor;
 use crate::error::ProcessorError;
 use crate::excel::ExcelReader;
 use crate::manifest::{ImportStep, InstanceStep, StepType};
-use crate::types::{IdOpt, JsonLdInstance, PropertyDatatype};
-use crate::utils::{to_kebab_case, to_pascal_case};
+use crate::types::{ExtraItem, Header, IdOpt, JsonLdInstance, OnEntity, PropertyDatatype};
+use crate::utils::{template_placeholders, to_pascal_case};
 use crate::{Manifest, ProcessingState};
-use serde_json::Map;
+use csv::StringRecord;
+use serde_json::{Map, Value as JsonValue};
 use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::mem::take;
-use uuid::Uuid;
 
 impl InstanceProcessor {
     pub async fn process_simple_instance(
@@ -21,6 +23,21 @@ impl InstanceProcessor {
             .iter()
             .any(|t| matches!(t, StepType::InstanceStep(InstanceStep::PicklistStep)));
 
+        let subclass_property = if step.types.iter().any(|t| {
+            matches!(
+                t,
+                StepType::InstanceStep(InstanceStep::SubClassInstanceStep)
+            )
+        }) {
+            Some(step.sub_class_property.as_ref().ok_or_else(|| {
+                ProcessorError::Processing(
+                    "SubClassInstanceStep requires subClassProperty field".into(),
+                )
+            })?)
+        } else {
+            None
+        };
+
         let mut class_type = step.instance_type.clone();
 
         let sheet_or_path_name = step.id();
@@ -49,7 +66,7 @@ impl InstanceProcessor {
 
         let mut rdr = csv::Reader::from_reader(csv_bytes.as_slice());
 
-        let headers: Vec<String> = rdr
+        let csv_headers: Vec<String> = rdr
             .headers()
             .map_err(|e| ProcessorError::Processing(format!("Failed to read CSV headers: {}", e)))?
             .iter()
@@ -59,7 +76,7 @@ impl InstanceProcessor {
         // TODO: This is bad... we need to think about a scenario like an excel spreadsheet where we can't know if each sheet is a model file or instance file
         // Right now, we're assuming that if the file is an excel file, this is the only scenario where we may have to guess if the file is a model file or instance file
         if self.manifest.excel_file.is_some()
-            && Manifest::is_model_file(headers.iter().map(|h| h.as_str()).collect())
+            && Manifest::is_model_file(csv_headers.iter().map(|h| h.as_str()).collect())
         {
             tracing::info!(
                 "CSV or sheet {} does not appear to be an instance file, skipping",
@@ -89,43 +106,101 @@ impl InstanceProcessor {
             .find(|over_ride| over_ride.map_to == "@id")
             .map(|over_ride| &over_ride.column);
 
-        let identifier_label = vocab.get_identifier_label(&class_type).or(override_label).ok_or_else(|| {
-            ProcessorError::Processing(format!(
-                "[Processing: {sheet_or_path_name}] No identifier property found for class '{class_type}'. Please check that your data model includes this class, or confirm file data is correct."
-            ))
-        })?.clone();
+        let identifier_label = vocab
+            .get_identifier_label(&class_type)
+            .or(override_label)
+            .cloned();
+
+        if identifier_label.is_none() && step.id_template.is_none() {
+            return Err(ProcessorError::Processing(format!(
+                "[Processing: {sheet_or_path_name}] No identifier property found for class '{class_type}'. Please check that your data model includes this class, provide an idTemplate, or confirm file data is correct."
+            )));
+        }
 
         if let Some(pivot_columns) = &step.pivot_columns {
             self.validate_pivot_columns(pivot_columns.iter().collect(), &class_type)?;
         };
-        tracing::info!("pre-validated-headers: {headers:?}");
+        if let Some(id_template) = &step.id_template {
+            self.validate_id_template(id_template, &csv_headers)?;
+        }
+        for pivot_column in step.pivot_columns.iter().flatten() {
+            if let Some(id_template) = &pivot_column.id_template {
+                self.validate_id_template(id_template, &csv_headers)?;
+            }
+        }
+
+        let repeating_patterns = RepeatingPatterns::compile(&step.repeating_groups)?;
+        tracing::info!("pre-validated-headers: {csv_headers:?}");
         let headers = self.validate_headers(
-            &headers,
+            &csv_headers,
             &class_type,
-            &identifier_label,
-            step.pivot_columns.as_ref(),
-            step.map_to_label.as_ref(),
+            identifier_label.as_deref(),
+            step,
+            &repeating_patterns,
         )?;
         tracing::info!("validated-headers: {headers:?}");
 
-        let id_column_index = headers
-            .iter()
-            .position(|h| {
-                h.as_ref()
-                    .map(|h| h.name == *identifier_label)
-                    .unwrap_or(false)
+        for nested in &step.nested {
+            if let Some(id_template) = &nested.id_template {
+                self.validate_id_template(id_template, &csv_headers)?;
+            }
+        }
+        let nested_columns = self.validate_nested_headers(&csv_headers, &class_type, step)?;
+
+        for group in &step.repeating_groups {
+            if let Some(id_template) = group.id_template_for("1") {
+                self.validate_id_template(&id_template, &csv_headers)?;
+            }
+        }
+        let repeating_columns =
+            self.validate_repeating_headers(&csv_headers, &class_type, step, &repeating_patterns)?;
+        let (extracted_references, extracted_columns) =
+            self.validate_extracted_headers(&csv_headers, step)?;
+        let lookups = self.build_lookup_indexes(step, &headers, &csv_headers)?;
+
+        let id_column_index = match (&step.id_template, &identifier_label) {
+            (Some(_), _) => None,
+            (None, Some(identifier_label)) => Some(
+                headers
+                    .iter()
+                    .position(|h| {
+                        h.as_ref()
+                            .map(|h| h.name == *identifier_label)
+                            .unwrap_or(false)
+                    })
+                    .ok_or_else(|| {
+                        ProcessorError::Processing(format!(
+                            "Identifier column '{}' not found in headers: {}",
+                            identifier_label,
+                            headers
+                                .iter()
+                                .filter_map(|opt_head| opt_head.as_ref().map(|h| h.name.clone()))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        ))
+                    })?,
+            ),
+            (None, None) => {
+                return Err(ProcessorError::Processing(format!(
+                    "No identifier property found for class '{}'",
+                    class_type
+                )))
+            }
+        };
+
+        let subclass_column_index = subclass_property
+            .map(|subclass_property| {
+                csv_headers
+                    .iter()
+                    .position(|h| h == subclass_property)
+                    .ok_or_else(|| {
+                        ProcessorError::Processing(format!(
+                            "Subclass property column '{}' not found in headers",
+                            subclass_property
+                        ))
+                    })
             })
-            .ok_or_else(|| {
-                ProcessorError::Processing(format!(
-                    "Identifier column '{}' not found in headers: {}",
-                    identifier_label,
-                    headers
-                        .iter()
-                        .filter_map(|opt_head| opt_head.as_ref().map(|h| h.name.clone()))
-                        .collect::<Vec<String>>()
-                        .join(", ")
-                ))
-            })?;
+            .transpose()?;
 
         let mut mismatched_rows = vec![];
 
@@ -145,13 +220,15 @@ impl InstanceProcessor {
                 }
             };
 
-            let id = match record.get(id_column_index) {
-                Some(id) if !id.is_empty() => id.to_string(),
-                _ => {
-                    let error = ProcessorError::Processing(format!(
-                        "Missing or empty identifier value at row {}",
-                        result_row_num + 1
-                    ));
+            let id = match self.row_identifier(
+                step.id_template.as_deref(),
+                id_column_index,
+                &csv_headers,
+                &record,
+                result_row_num,
+            ) {
+                Ok(id) => self.namespaced_id(&class_type, &id),
+                Err(error) => {
                     if self.is_strict {
                         self.processing_state.add_error_from(error);
                         continue;
@@ -162,11 +239,49 @@ impl InstanceProcessor {
                 }
             };
 
-            let id = if self.is_namespace_iris {
-                format!("{}/{}", to_kebab_case(&class_type), &id)
-            } else {
-                id
-            };
+            let mut types = vec![IdOpt::String(class_type.clone())];
+            if let Some(subclass_column_index) = subclass_column_index {
+                match self.subclass_type(record.get(subclass_column_index), result_row_num) {
+                    Some(subclass_type) => types.push(subclass_type),
+                    None => continue,
+                }
+            }
+
+            let mut pivot_ids = HashMap::new();
+            for pivot_column in step.pivot_columns.iter().flatten() {
+                if pivot_ids.contains_key(&pivot_column.new_relationship_property) {
+                    continue;
+                }
+                // Groups without any values produce no instance, so they need no identifier
+                let has_values = pivot_column.columns.iter().any(|column| {
+                    csv_headers
+                        .iter()
+                        .position(|h| h == column)
+                        .and_then(|i| record.get(i))
+                        .is_some_and(|value| !value.is_empty())
+                });
+                if !has_values {
+                    continue;
+                }
+                match self.pivot_identifier(
+                    pivot_column,
+                    &id,
+                    &csv_headers,
+                    &record,
+                    result_row_num,
+                ) {
+                    Ok(pivot_id) => {
+                        pivot_ids.insert(pivot_column.new_relationship_property.clone(), pivot_id);
+                    }
+                    Err(error) => {
+                        if self.is_strict {
+                            self.processing_state.add_error_from(error);
+                        } else {
+                            self.processing_state.add_warning_from(error);
+                        }
+                    }
+                }
+            }
 
             let mut properties = Map::new();
 
@@ -185,19 +300,7 @@ impl InstanceProcessor {
                                     })
                                 });
 
-                            // TODO: Fix this
-                            let default_delimiter = ";".to_string();
-                            let vec_value = if let Some(delimiter) =
-                                step.delimit_values_on.as_ref().or(Some(&default_delimiter))
-                            {
-                                if header.datatype == crate::types::PropertyDatatype::String {
-                                    vec![value]
-                                } else {
-                                    value.split(delimiter.as_str()).map(|s| s.trim()).collect()
-                                }
-                            } else {
-                                vec![value]
-                            };
+                            let vec_value = split_values(step, &header.datatype, value);
 
                             let mut final_values = vec![];
 
@@ -206,12 +309,19 @@ impl InstanceProcessor {
                             }
 
                             for value in vec_value {
-                                let processed_value = match self.process_value(
-                                    value,
-                                    &header.datatype,
-                                    &header.name,
-                                    result_row_num,
-                                ) {
+                                let processed_value = match &lookups[i] {
+                                    Some(lookup) => self
+                                        .resolve_lookup(lookup, value, &header.name, result_row_num)
+                                        .map(JsonValue::String),
+                                    None => self.process_value(
+                                        value,
+                                        header,
+                                        &csv_headers[i],
+                                        &header.name,
+                                        result_row_num,
+                                    ),
+                                };
+                                let processed_value = match processed_value {
                                     Ok(value) => value,
                                     Err(e) => {
                                         if self.is_strict {
@@ -223,14 +333,22 @@ impl InstanceProcessor {
                                         }
                                     }
                                 };
-                                final_values.push(processed_value);
+                                final_values.push(language_tagged(processed_value, header));
+                            }
+                            if final_values.is_empty() {
+                                continue;
                             }
 
                             if let Some(pivot_column_match) = is_pivot_header {
+                                let Some(pivot_id) =
+                                    pivot_ids.get(&pivot_column_match.new_relationship_property)
+                                else {
+                                    continue;
+                                };
                                 let pivot_property_entry = properties
                                     .entry(pivot_column_match.new_relationship_property.clone())
                                     .or_insert_with(|| {
-                                        let id = Uuid::new_v4().to_string();
+                                        let id = pivot_id.clone();
                                         let mut new_map = Map::new();
                                         new_map.insert(
                                             "@id".to_string(),
@@ -265,24 +383,62 @@ impl InstanceProcessor {
                                     }
                                 };
                             } else if header.is_label_header {
-                                properties.insert(header.name.clone(), final_values.clone().into());
-                                properties.insert("label".to_string(), final_values.into());
+                                append_property_values(
+                                    &mut properties,
+                                    &header.name,
+                                    final_values.clone(),
+                                );
+                                append_property_values(&mut properties, "label", final_values);
                             } else {
-                                properties.insert(header.name.clone(), final_values.into());
+                                append_property_values(&mut properties, &header.name, final_values);
                             }
                         }
                     }
                 }
             }
 
+            for (property, object) in self.nested_values(
+                step,
+                &nested_columns,
+                &csv_headers,
+                &record,
+                &id,
+                result_row_num,
+            ) {
+                append_property_values(&mut properties, &property, vec![object]);
+            }
+
+            for (property, reference) in self.repeating_group_instances(
+                step,
+                &repeating_columns,
+                &csv_headers,
+                &record,
+                &id,
+                result_row_num,
+            ) {
+                append_property_values(&mut properties, &property, vec![reference]);
+            }
+
+            self.extract_entities(
+                step,
+                &extracted_references,
+                &extracted_columns,
+                &csv_headers,
+                &record,
+                result_row_num,
+            );
+
+            apply_extra_items(&step.extra_items, &csv_headers, &record, &mut properties);
+
             let instance_id = IdOpt::String(id);
 
             let instance = JsonLdInstance {
                 id: instance_id.clone(),
-                type_: vec![IdOpt::String(class_type.clone())],
+                type_: types,
                 properties,
             };
 
+            self.record_origin(&instance.id.to_string(), result_row_num);
             if let Err(e) = self.update_or_insert_instance(instance.clone()) {
                 let msg = format!("Failed to update/insert instance {}: {}", instance.id, e);
                 if self.is_strict {
@@ -337,29 +493,29 @@ impl InstanceProcessor {
         Ok(take(&mut self.processing_state))
     }
 
+    /// Subclass instance steps are processed like basic instance steps, with each row's
+    /// `subClassProperty` value added to the instance's types
     pub async fn process_subclass_instance(
         &mut self,
         step: &ImportStep,
         s3_client: Option<&aws_sdk_s3::Client>,
     ) -> Result<ProcessingState, ProcessorError> {
-        // TODO: Handle ProcessingState here the way we did for process_simple_instance
-        // Get the parent class type from the manifest
-        let parent_class_type = step.instance_type.clone();
+        self.process_simple_instance(step, s3_client).await
+    }
 
-        let sheet_or_path_name = step.id();
+    pub async fn process_properties_instance(
+        &mut self,
+        step: &ImportStep,
+        s3_client: Option<&aws_sdk_s3::Client>,
+    ) -> Result<ProcessingState, ProcessorError> {
+        let class_type = step.instance_type.clone();
 
-        let subclass_property = step.sub_class_property.as_ref().ok_or_else(|| {
-            ProcessorError::Processing(
-                "SubClassInstanceStep requires subClassProperty field".into(),
-            )
-        })?;
+        let sheet_or_path_name = step.id();
 
         let vocab = self.vocabulary.as_ref().ok_or_else(|| {
             ProcessorError::Processing("Vocabulary must be set before processing instances".into())
         })?;
 
-        tracing::debug!("Getting identifier label for class '{}'", parent_class_type);
-
         let override_label = step
             .overrides
             .iter()
@@ -367,16 +523,30 @@ impl InstanceProcessor {
             .map(|over_ride| &over_ride.column);
 
         let identifier_label = vocab
-            .get_identifier_label(&parent_class_type)
+            .get_identifier_label(&class_type)
             .or(override_label)
             .ok_or_else(|| {
                 ProcessorError::Processing(format!(
                     "No identifier property found for class '{}'",
-                    parent_class_type
+                    class_type
                 ))
             })?
             .clone();
 
+        let property_id_column = step
+            .overrides
+            .iter()
+            .find(|o| o.map_to == "$Property.ID")
+            .map(|o| o.column.as_str())
+            .unwrap_or("Property ID");
+
+        let property_value_column = step
+            .overrides
+            .iter()
+            .find(|o| o.map_to == "$Property.Value")
+            .map(|o| o.column.as_str())
+            .unwrap_or("Property Value");
+
         tracing::debug!("Reading instance data from {:?}", &sheet_or_path_name);
 
         let csv_bytes = if let Some(sheet_name) = &step.sheet {
@@ -398,10 +568,11 @@ impl InstanceProcessor {
 
         let mut rdr = csv::Reader::from_reader(csv_bytes.as_slice());
 
-        let headers = rdr
-            .headers()
-            .map_err(|e| ProcessorError::Processing(format!("Failed to read CSV headers: {}", e)))?
-            .clone();
+        let headers = rdr.headers().map_err(|e| {
+            ProcessorError::Processing(format!("Failed to read CSV headers: {}", e))
+        })?;
+
+        let csv_headers: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
 
         let id_column_index = headers
             .iter()
@@ -409,17 +580,27 @@ impl InstanceProcessor {
             .ok_or_else(|| {
                 ProcessorError::Processing(format!(
                     "Identifier column '{}' not found in headers of CSV/sheet ({}): {:#?}",
-                    identifier_label, &sheet_or_path_name, headers
+                    identifier_label, sheet_or_path_name, headers
                 ))
             })?;
 
-        let subclass_column_index = headers
+        let property_id_index = headers
             .iter()
-            .position(|h| h == subclass_property)
+            .position(|h| h == property_id_column)
             .ok_or_else(|| {
                 ProcessorError::Processing(format!(
-                    "Subclass property column '{}' not found in headers",
-                    subclass_property
+                    "Property ID column '{}' not found in headers",
+                    property_id_column
+                ))
+            })?;
+
+        let property_value_index = headers
+            .iter()
+            .position(|h| h == property_value_column)
+            .ok_or_else(|| {
+                ProcessorError::Processing(format!(
+                    "Property Value column '{}' not found in headers",
+                    property_value_column
                 ))
             })?;
 
@@ -427,145 +608,96 @@ impl InstanceProcessor {
             let record = match result {
                 Ok(record) => record,
                 Err(e) => {
-                    let msg = format!("Failed to read CSV record: {}", e);
+                    let error =
+                        ProcessorError::Processing(format!("Failed to read CSV record: {}", e));
                     if self.is_strict {
-                        return Err(ProcessorError::Processing(msg));
+                        self.processing_state.add_error_from(error);
                     } else {
-                        self.processing_state.add_warning(
-                            format!("{}, skipping row", msg),
-                            Some("csv_processing".to_string()),
-                        );
-                        continue;
+                        self.processing_state.add_warning_from(error);
                     }
+                    continue;
                 }
             };
 
-            let id = match record.get(id_column_index) {
-                Some(id) if !id.is_empty() => id.to_string(),
-                _ => {
-                    let msg = format!(
-                        "Missing or empty identifier value at row {}",
-                        result_row_num + 1
-                    );
+            let entity_id = match self.row_identifier(
+                None,
+                Some(id_column_index),
+                &csv_headers,
+                &record,
+                result_row_num,
+            ) {
+                Ok(id) => self.namespaced_id(&class_type, &id),
+                Err(error) => {
                     if self.is_strict {
-                        return Err(ProcessorError::Processing(msg));
+                        self.processing_state.add_error_from(error);
                     } else {
-                        self.processing_state.add_warning(
-                            format!("{}, skipping row", msg),
-                            Some("instance_processing".to_string()),
-                        );
-                        continue;
+                        self.processing_state.add_warning_from(error);
                     }
+                    continue;
                 }
             };
 
-            let id = if self.is_namespace_iris {
-                format!("{}/{}", to_kebab_case(&parent_class_type), &id)
-            } else {
-                id
-            };
-
-            let subclass_ref = match record.get(subclass_column_index) {
-                Some(value) if !value.is_empty() => value,
+            let property_id = match record.get(property_id_index) {
+                Some(id) if !id.is_empty() => id,
                 _ => {
-                    let msg = format!(
-                        "Missing or empty subclass reference at row {}",
+                    let error = ProcessorError::Processing(format!(
+                        "Missing or empty Property ID at row {}, skipping row",
                         result_row_num + 1
-                    );
+                    ));
                     if self.is_strict {
-                        return Err(ProcessorError::Processing(msg));
+                        self.processing_state.add_error_from(error);
                     } else {
-                        self.processing_state.add_warning(
-                            format!("{}, skipping row", msg),
-                            Some("instance_processing".to_string()),
-                        );
-                        continue;
+                        self.processing_state.add_warning_from(error);
                     }
+                    continue;
                 }
             };
 
-            let subclass_ref = self
-                .vocabulary
-                .as_ref()
-                .unwrap()
-                .classes
-                .iter()
-                .find_map(|(id, _)| match id {
-                    IdOpt::String(string_id) if string_id == subclass_ref => Some(id.clone()),
-                    IdOpt::ReplacementMap { original_id, .. } if original_id == subclass_ref => {
-                        Some(id.clone())
-                    }
-                    _ => None,
-                })
-                .unwrap_or_else(|| {
-                    let msg = format!(
-                        "Subclass reference '{}' not found in vocabulary",
-                        subclass_ref
-                    );
+            let property_value = match record.get(property_value_index) {
+                Some(value) if !value.is_empty() => value,
+                _ => {
+                    let error = ProcessorError::Processing(format!(
+                        "Missing or empty Property Value at row {}, skipping row",
+                        result_row_num + 1
+                    ));
                     if self.is_strict {
-                        panic!("{}", msg); // This will be caught and converted to an error
+                        self.processing_state.add_error_from(error);
                     } else {
-                        self.processing_state.add_warning(
-                            format!("{}, using raw value", msg),
-                            Some("instance_processing".to_string()),
-                        );
-                        IdOpt::String(subclass_ref.to_string())
+                        self.processing_state.add_warning_from(error);
                     }
-                })
-                .normalize()
-                .to_pascal_case();
-
-            let mut properties = Map::new();
-
-            if headers.len() != record.len() {
-                let msg = format!(
-                    "Row {} has different number of columns than headers: RECORD: {} HEADERS: {}",
-                    result_row_num + 1,
-                    record.len(),
-                    headers.len()
-                );
-                if self.is_strict {
-                    return Err(ProcessorError::Processing(msg));
-                } else {
-                    self.processing_state.add_warning(
-                        format!("{}, skipping row", msg),
-                        Some("csv_processing".to_string()),
-                    );
                     continue;
                 }
-            }
-
-            for (i, header) in headers.iter().enumerate() {
-                if i != id_column_index && i != subclass_column_index {
-                    if let Some(value) = record.get(i) {
-                        if !value.is_empty() {
-                            // TODO: Process value based on property type from vocabulary
-                            // For now, just store as string
-                            properties.insert(
-                                header.to_string(),
-                                serde_json::Value::String(value.to_string()),
-                            );
-                        }
-                    }
-                }
-            }
+            };
 
-            let instance = JsonLdInstance {
-                id: IdOpt::String(id),
-                type_: vec![IdOpt::String(parent_class_type.clone()), subclass_ref],
-                properties,
+            let Some(mut instance) = self.entity_value_instance(
+                step,
+                &class_type,
+                &entity_id,
+                property_id,
+                property_id,
+                property_value,
+                result_row_num,
+            ) else {
+                continue;
             };
 
-            if let Err(e) = self.update_or_insert_instance(instance.clone()) {
-                let msg = format!("Failed to update/insert instance {}: {}", instance.id, e);
+            apply_extra_items(
+                &step.extra_items,
+                &csv_headers,
+                &record,
+                &mut instance.properties,
+            );
+
+            self.record_origin(&entity_id, result_row_num);
+            if let Err(e) = self.update_or_insert_instance(instance) {
+                let error = ProcessorError::Processing(format!(
+                    "Failed to update/insert instance {}: {}",
+                    entity_id, e
+                ));
                 if self.is_strict {
-                    return Err(ProcessorError::Processing(msg));
+                    self.processing_state.add_error_from(error);
                 } else {
-                    self.processing_state.add_warning(
-                        format!("{}, skipping instance", msg),
-                        Some("instance_processing".to_string()),
-                    );
-                    continue;
+                    self.processing_state.add_warning_from(error);
                 }
             }
         }
@@ -573,12 +705,13 @@ impl InstanceProcessor {
         Ok(take(&mut self.processing_state))
     }
 
-    pub async fn process_properties_instance(
+    /// Unpivot wide rows (one entity per row, one column per property) into entity/attribute/value
+    /// triples, processed the same way as a PropertiesInstanceStep
+    pub async fn process_unpivot_instance(
         &mut self,
         step: &ImportStep,
         s3_client: Option<&aws_sdk_s3::Client>,
     ) -> Result<ProcessingState, ProcessorError> {
-        // TODO: Handle ProcessingState here the way we did for process_simple_instance
         let class_type = step.instance_type.clone();
 
         let sheet_or_path_name = step.id();
@@ -596,27 +729,14 @@ impl InstanceProcessor {
         let identifier_label = vocab
             .get_identifier_label(&class_type)
             .or(override_label)
-            .ok_or_else(|| {
-                ProcessorError::Processing(format!(
-                    "No identifier property found for class '{}'",
-                    class_type
-                ))
-            })?
-            .clone();
+            .cloned();
 
-        let property_id_column = step
-            .overrides
-            .iter()
-            .find(|o| o.map_to == "$Property.ID")
-            .map(|o| o.column.as_str())
-            .unwrap_or("Property ID");
-
-        let property_value_column = step
-            .overrides
-            .iter()
-            .find(|o| o.map_to == "$Property.Value")
-            .map(|o| o.column.as_str())
-            .unwrap_or("Property Value");
+        if identifier_label.is_none() && step.id_template.is_none() {
+            return Err(ProcessorError::Processing(format!(
+                "No identifier property found for class '{}'",
+                class_type
+            )));
+        }
 
         tracing::debug!("Reading instance data from {:?}", &sheet_or_path_name);
 
@@ -639,175 +759,294 @@ impl InstanceProcessor {
 
         let mut rdr = csv::Reader::from_reader(csv_bytes.as_slice());
 
-        let headers = rdr.headers().map_err(|e| {
-            ProcessorError::Processing(format!("Failed to read CSV headers: {}", e))
-        })?;
-
-        let id_column_index = headers
+        let csv_headers: Vec<String> = rdr
+            .headers()
+            .map_err(|e| ProcessorError::Processing(format!("Failed to read CSV headers: {}", e)))?
             .iter()
-            .position(|h| h == identifier_label)
-            .ok_or_else(|| {
-                ProcessorError::Processing(format!(
-                    "Identifier column '{}' not found in headers of CSV/sheet ({}): {:#?}",
-                    identifier_label, sheet_or_path_name, headers
-                ))
-            })?;
+            .map(|h| h.to_string())
+            .collect();
 
-        let property_id_index = headers
-            .iter()
-            .position(|h| h == property_id_column)
-            .ok_or_else(|| {
-                ProcessorError::Processing(format!(
-                    "Property ID column '{}' not found in headers",
-                    property_id_column
-                ))
-            })?;
+        let id_column_index = match (&step.id_template, &identifier_label) {
+            (Some(id_template), _) => {
+                self.validate_id_template(id_template, &csv_headers)?;
+                None
+            }
+            (None, Some(identifier_label)) => Some(
+                csv_headers
+                    .iter()
+                    .position(|h| h == identifier_label)
+                    .ok_or_else(|| {
+                        ProcessorError::Processing(format!(
+                            "Identifier column '{}' not found in headers of CSV/sheet ({}): {:?}",
+                            identifier_label, &sheet_or_path_name, csv_headers
+                        ))
+                    })?,
+            ),
+            (None, None) => {
+                return Err(ProcessorError::Processing(format!(
+                    "No identifier property found for class '{}'",
+                    class_type
+                )))
+            }
+        };
 
-        let property_value_index = headers
-            .iter()
-            .position(|h| h == property_value_column)
-            .ok_or_else(|| {
-                ProcessorError::Processing(format!(
-                    "Property Value column '{}' not found in headers",
-                    property_value_column
-                ))
-            })?;
+        // The columns to unpivot, paired with the property each one holds
+        let ignorable_headers = self.ignore.get(&class_type).cloned().unwrap_or_default();
+        let template_columns = step
+            .id_template
+            .as_deref()
+            .map(template_placeholders)
+            .transpose()?
+            .unwrap_or_default();
+        let unpivot_columns: Vec<(usize, String)> = match &step.unpivot_columns {
+            Some(columns) => {
+                let mut unpivot_columns = Vec::with_capacity(columns.len());
+                for column in columns {
+                    let index = csv_headers
+                        .iter()
+                        .position(|h| h == column)
+                        .ok_or_else(|| {
+                            ProcessorError::Processing(format!(
+                                "Unpivot column '{}' not found in headers of CSV/sheet ({})",
+                                column, &sheet_or_path_name
+                            ))
+                        })?;
+                    unpivot_columns.push((index, column.clone()));
+                }
+                unpivot_columns
+            }
+            None => csv_headers
+                .iter()
+                .enumerate()
+                .filter(|(i, header)| {
+                    Some(*i) != id_column_index
+                        && !header.is_empty()
+                        && !ignorable_headers.contains(header)
+                        && !template_columns.contains(header)
+                        && !step.extra_items.iter().any(|item| &item.column == *header)
+                })
+                .map(|(i, header)| (i, header.clone()))
+                .collect(),
+        };
+        let unpivot_columns: Vec<(usize, String)> = unpivot_columns
+            .into_iter()
+            .map(|(i, column)| {
+                let property_ref = step
+                    .overrides
+                    .iter()
+                    .find(|o| o.column == column && o.map_to != "@id" && !o.map_to.starts_with('$'))
+                    .map(|o| o.map_to.clone())
+                    .unwrap_or(column);
+                (i, property_ref)
+            })
+            .collect();
 
         for (result_row_num, result) in rdr.records().enumerate() {
             let record = match result {
                 Ok(record) => record,
                 Err(e) => {
-                    let msg = format!("Failed to read CSV record: {}", e);
+                    let error =
+                        ProcessorError::Processing(format!("Failed to read CSV record: {}", e));
                     if self.is_strict {
-                        return Err(ProcessorError::Processing(msg));
+                        self.processing_state.add_error_from(error);
                     } else {
-                        self.processing_state.add_warning(
-                            format!("{}, skipping row", msg),
-                            Some("csv_processing".to_string()),
-                        );
-                        continue;
+                        self.processing_state.add_warning_from(error);
                     }
+                    continue;
                 }
             };
 
-            let entity_id = match record.get(id_column_index) {
-                Some(id) if !id.is_empty() => id.to_string(),
-                _ => {
-                    let msg = format!(
-                        "Missing or empty identifier value at row {}",
-                        result_row_num + 1
-                    );
+            let entity_id = match self.row_identifier(
+                step.id_template.as_deref(),
+                id_column_index,
+                &csv_headers,
+                &record,
+                result_row_num,
+            ) {
+                Ok(id) => self.namespaced_id(&class_type, &id),
+                Err(error) => {
                     if self.is_strict {
-                        return Err(ProcessorError::Processing(msg));
+                        self.processing_state.add_error_from(error);
                     } else {
-                        self.processing_state.add_warning(
-                            format!("{}, skipping row", msg),
-                            Some("instance_processing".to_string()),
-                        );
-                        continue;
+                        self.processing_state.add_warning_from(error);
                     }
+                    continue;
                 }
             };
 
-            let entity_id = if self.is_namespace_iris {
-                format!("{}/{}", to_kebab_case(&class_type), entity_id)
-            } else {
-                entity_id
-            };
+            for (index, property_ref) in &unpivot_columns {
+                let Some(value) = record.get(*index).filter(|value| !value.is_empty()) else {
+                    continue;
+                };
+
+                let Some(mut instance) = self.entity_value_instance(
+                    step,
+                    &class_type,
+                    &entity_id,
+                    property_ref,
+                    &csv_headers[*index],
+                    value,
+                    result_row_num,
+                ) else {
+                    continue;
+                };
 
-            let property_id = match record.get(property_id_index) {
-                Some(id) if !id.is_empty() => id,
-                _ => {
-                    let msg = format!("Missing or empty Property ID at row {}", result_row_num + 1);
+                apply_extra_items(
+                    &step.extra_items,
+                    &csv_headers,
+                    &record,
+                    &mut instance.properties,
+                );
+
+                self.record_origin(&entity_id, result_row_num);
+                if let Err(e) = self.update_or_insert_instance(instance) {
+                    let error = ProcessorError::Processing(format!(
+                        "Failed to update/insert instance {}: {}",
+                        entity_id, e
+                    ));
                     if self.is_strict {
-                        return Err(ProcessorError::Processing(msg));
+                        self.processing_state.add_error_from(error);
                     } else {
-                        self.processing_state.add_warning(
-                            format!("{}, skipping row", msg),
-                            Some("instance_processing".to_string()),
-                        );
-                        continue;
+                        self.processing_state.add_warning_from(error);
                     }
                 }
-            };
+            }
+        }
 
-            let property_id = vocab
-                .properties
-                .keys()
-                .find_map(|id| match id {
-                    IdOpt::String(string_id) if string_id == property_id => Some(id.clone()),
-                    IdOpt::ReplacementMap { original_id, .. } if original_id == property_id => {
-                        Some(id.clone())
-                    }
-                    _ => None,
-                })
-                .unwrap_or_else(|| {
-                    let msg = format!("Property ID '{}' not found in vocabulary", property_id);
-                    if self.is_strict {
-                        panic!("{}", msg); // This will be caught and converted to an error
-                    } else {
-                        self.processing_state.add_warning(
-                            format!("{}, using raw value", msg),
-                            Some("instance_processing".to_string()),
-                        );
-                        IdOpt::String(property_id.to_string())
-                    }
-                });
+        Ok(take(&mut self.processing_state))
+    }
 
-            let property_value = match record.get(property_value_index) {
-                Some(value) if !value.is_empty() => value,
-                _ => {
-                    let msg = format!(
-                        "Missing or empty Property Value at row {}",
-                        result_row_num + 1
-                    );
+    /// Build the instance fragment for a single entity/attribute/value triple, converting the
+    /// value according to the referenced property's range, with the parse profile of `column`.
+    /// Returns `None` when the value should be skipped
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn entity_value_instance(
+        &mut self,
+        step: &ImportStep,
+        class_type: &str,
+        entity_id: &str,
+        property_ref: &str,
+        column: &str,
+        value: &str,
+        row_num: usize,
+    ) -> Option<JsonLdInstance> {
+        let header = match self.find_property(property_ref) {
+            Some(property) => Header::try_from(property).unwrap_or_else(|_| Header {
+                name: property.id.final_iri(),
+                datatype: property
+                    .range
+                    .as_ref()
+                    .and_then(|range| range.first().cloned())
+                    .unwrap_or(PropertyDatatype::String),
+                is_label_header: false,
+                unit: property.unit.clone(),
+                language: None,
+                constraints: property.constraints.clone(),
+            }),
+            None => {
+                let msg = format!(
+                    "Property ID '{}' not found in vocabulary at row {}",
+                    property_ref,
+                    row_num + 1
+                );
+                if self.is_strict {
+                    self.processing_state
+                        .add_error_from(ProcessorError::Processing(msg));
+                    return None;
+                }
+                self.processing_state.add_warning(
+                    format!("{}, using raw value", msg),
+                    Some("instance_processing".to_string()),
+                );
+                Header {
+                    name: property_ref.to_string(),
+                    ..Default::default()
+                }
+            }
+        };
+
+        let mut values = vec![];
+        for value in split_values(step, &header.datatype, value) {
+            match self.process_value(value, &header, column, &header.name, row_num) {
+                Ok(value) => values.push(value),
+                Err(e) => {
                     if self.is_strict {
-                        return Err(ProcessorError::Processing(msg));
+                        self.processing_state.add_error_from(e);
                     } else {
-                        self.processing_state.add_warning(
-                            format!("{}, skipping row", msg),
-                            Some("instance_processing".to_string()),
-                        );
-                        continue;
+                        self.processing_state.add_warning_from(e);
                     }
                 }
-            };
+            }
+        }
+        if values.is_empty() {
+            return None;
+        }
 
-            self.instances
-                .entry(entity_id.to_string())
-                .and_modify(|instance| {
-                    instance
-                        .properties
-                        .entry(property_id.final_iri())
-                        .and_modify(|current| {
-                            if current.is_array() {
-                                let array = current.as_array_mut().unwrap();
-                                array.push(serde_json::Value::String(property_value.to_string()));
-                            } else {
-                                let array = vec![
-                                    current.take(),
-                                    serde_json::Value::String(property_value.to_string()),
-                                ];
-                                *current = serde_json::Value::Array(array);
-                            }
-                        })
-                        .or_insert_with(|| serde_json::Value::String(property_value.to_string()));
-                })
-                .or_insert_with(|| {
-                    let mut properties = Map::new();
-                    properties.insert(
-                        property_id.final_iri(),
-                        serde_json::Value::String(property_value.to_string()),
+        let mut properties = Map::new();
+        properties.insert(header.name, values.into());
+        Some(JsonLdInstance {
+            id: IdOpt::String(entity_id.to_string()),
+            type_: vec![IdOpt::String(class_type.to_string())],
+            properties,
+        })
+    }
+
+    /// Resolve a row's subclass reference against the vocabulary's classes. Returns `None` when
+    /// the row should be skipped
+    fn subclass_type(&mut self, subclass_ref: Option<&str>, row_num: usize) -> Option<IdOpt> {
+        let subclass_ref = match subclass_ref {
+            Some(value) if !value.is_empty() => value,
+            _ => {
+                let error = ProcessorError::Processing(format!(
+                    "Missing or empty subclass reference at row {}, skipping row",
+                    row_num + 1
+                ));
+                if self.is_strict {
+                    self.processing_state.add_error_from(error);
+                } else {
+                    self.processing_state.add_warning_from(error);
+                }
+                return None;
+            }
+        };
+
+        let subclass_id = self
+            .vocabulary
+            .as_ref()
+            .unwrap()
+            .classes
+            .keys()
+            .find(|id| match id {
+                IdOpt::String(string_id) => string_id == subclass_ref,
+                IdOpt::ReplacementMap { original_id, .. } => original_id == subclass_ref,
+            })
+            .cloned();
+
+        match subclass_id {
+            Some(subclass_id) => Some(subclass_id.normalize().to_pascal_case()),
+            None => {
+                let msg = format!(
+                    "Subclass reference '{}' not found in vocabulary at row {}",
+                    subclass_ref,
+                    row_num + 1
+                );
+                if self.is_strict {
+                    self.processing_state
+                        .add_error_from(ProcessorError::Processing(msg));
+                    None
+                } else {
+                    self.processing_state.add_warning(
+                        format!("{}, using raw value", msg),
+                        Some("instance_processing".to_string()),
                     );
-                    JsonLdInstance {
-                        id: IdOpt::String(entity_id),
-                        type_: vec![IdOpt::String(class_type.clone())],
-                        properties,
-                    }
-                });
+                    Some(
+                        IdOpt::String(subclass_ref.to_string())
+                            .normalize()
+                            .to_pascal_case(),
+                    )
+                }
+            }
         }
-
-        Ok(take(&mut self.processing_state))
     }
 
     pub(crate) fn update_or_insert_instance(
@@ -828,3 +1067,338 @@ impl InstanceProcessor {
         Ok(())
     }
 }
+
+/// Split a cell into its values on the step's delimiter (`;` by default). String values are
+/// never split
+pub(super) fn split_values<'a>(
+    step: &ImportStep,
+    datatype: &PropertyDatatype,
+    value: &'a str,
+) -> Vec<&'a str> {
+    // TODO: Fix this
+    let default_delimiter = ";".to_string();
+    if let Some(delimiter) = step.delimit_values_on.as_ref().or(Some(&default_delimiter)) {
+        if matches!(
+            datatype,
+            PropertyDatatype::String | PropertyDatatype::LangString
+        ) {
+            vec![value]
+        } else {
+            value.split(delimiter.as_str()).map(|s| s.trim()).collect()
+        }
+    } else {
+        vec![value]
+    }
+}
+
+/// Add values to a property, keeping any values already contributed by another column that maps
+/// to the same property
+pub(super) fn append_property_values(
+    properties: &mut Map<String, JsonValue>,
+    key: &str,
+    values: Vec<JsonValue>,
+) {
+    match properties.get_mut(key) {
+        Some(JsonValue::Array(existing)) => {
+            for value in values {
+                if !existing.contains(&value) {
+                    existing.push(value);
+                }
+            }
+        }
+        _ => {
+            properties.insert(key.to_string(), values.into());
+        }
+    }
+}
+
+/// Wrap a string value of a language-tagged column in a value object carrying its language
+fn language_tagged(value: JsonValue, header: &Header) -> JsonValue {
+    match (value, &header.language) {
+        (JsonValue::String(value), Some(language)) => {
+            serde_json::json!({ "@value": value, "@language": language })
+        }
+        (value, _) => value,
+    }
+}
+
+/// Attach a step's INSTANCE extraItems to an instance, using the item's column value for the row
+/// when a column is given, and its static value otherwise
+fn apply_extra_items(
+    extra_items: &[ExtraItem],
+    headers: &[String],
+    record: &StringRecord,
+    properties: &mut Map<String, JsonValue>,
+) {
+    for extra_item in extra_items
+        .iter()
+        .filter(|item| matches!(item.on_entity, OnEntity::Instance))
+    {
+        let value = if extra_item.column.is_empty() {
+            extra_item.value.as_deref()
+        } else {
+            headers
+                .iter()
+                .position(|h| *h == extra_item.column)
+                .and_then(|i| record.get(i))
+        };
+        if let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) {
+            append_property_values(
+                properties,
+                &extra_item.map_to,
+                vec![JsonValue::String(value.to_string())],
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, ImportRun, TestManifest};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_instance_extra_items() {
+        let manifest = TestManifest::new().instance_step(
+            "products.csv",
+            "BasicInstanceStep",
+            json!({
+                "instanceType": "Product",
+                "extraItems": [
+                    {
+                        "mapTo": "http://example.com/terms/sourceSystem",
+                        "onEntity": "INSTANCE",
+                        "value": "ERP"
+                    },
+                    {
+                        "column": "Data Owner",
+                        "mapTo": "http://example.com/terms/dataOwner",
+                        "onEntity": "INSTANCE"
+                    }
+                ]
+            }),
+        );
+        let model = model_csv(
+            &[],
+            "\
+Product,Product ID,Product,A product,Product ID,Identifier,@id,
+Product,Product Name,Product,A product,Product Name,Display name,String,
+",
+        );
+        let products = "Product ID,Product Name,Data Owner\nP1,Screw,Finance\nP2,Bolt,\n";
+        let run = run_import(
+            &manifest.build(),
+            &[("model.csv", &model), ("products.csv", products)],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let screw = run.instance("P1").unwrap();
+        assert_eq!(
+            screw["http://example.com/terms/sourceSystem"],
+            json!(["ERP"])
+        );
+        assert_eq!(
+            screw["http://example.com/terms/dataOwner"],
+            json!(["Finance"])
+        );
+        // The extra item column is not treated as an unknown property
+        assert!(screw.get("Data Owner").is_none());
+
+        let bolt = run.instance("P2").unwrap();
+        assert_eq!(
+            bolt["http://example.com/terms/sourceSystem"],
+            json!(["ERP"])
+        );
+        assert!(bolt.get("http://example.com/terms/dataOwner").is_none());
+    }
+
+    /// Import `files` with the Vehicle model and its Truck subclass
+    async fn import_with_vehicle_model(
+        manifest: TestManifest,
+        files: &[(&str, &str)],
+        is_strict: bool,
+    ) -> ImportRun {
+        let manifest = manifest.model_step("truck.csv", json!({ "subClassOf": ["Vehicle"] }));
+        let vehicle = model_csv(
+            &[],
+            "\
+Vehicle,Vehicle ID,Vehicle,A vehicle,Vehicle ID,Identifier,@id,
+Vehicle,Wheels,Vehicle,A vehicle,Wheels,Number of wheels,Integer,
+",
+        );
+        let truck = model_csv(
+            &[],
+            "\
+Truck,Payload,Truck,A truck,Payload,Payload in tonnes,Decimal,
+Truck,Refrigerated,Truck,A truck,Refrigerated,Whether it is refrigerated,Boolean,
+",
+        );
+        let mut all_files = vec![
+            ("model.csv", vehicle.as_str()),
+            ("truck.csv", truck.as_str()),
+        ];
+        all_files.extend_from_slice(files);
+        run_import(&manifest.build(), &all_files, is_strict).await
+    }
+
+    async fn import_vehicles(vehicles: &str, is_strict: bool) -> ImportRun {
+        let manifest = TestManifest::new().instance_step(
+            "vehicles.csv",
+            "SubClassInstanceStep",
+            json!({
+                "instanceType": "Vehicle",
+                "subClassProperty": "Kind",
+                "delimitValuesOn": ";"
+            }),
+        );
+        import_with_vehicle_model(manifest, &[("vehicles.csv", vehicles)], is_strict).await
+    }
+
+    #[tokio::test]
+    async fn test_subclass_instances_are_typed() {
+        let vehicles = "\
+Vehicle ID,Kind,Wheels,Payload,Refrigerated
+V1,Truck,6,12.5;8,true
+";
+        let run = import_vehicles(vehicles, true).await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let truck = run.instance("V1").unwrap();
+        assert_eq!(truck["@type"], json!(["Vehicle", "Truck"]));
+        // Both the parent class's and the subclass's properties are converted by datatype
+        assert_eq!(truck["Wheels"], json!([6]));
+        assert_eq!(truck["Payload"], json!(["12.5", "8"]));
+        assert_eq!(truck["Refrigerated"], json!([true]));
+        assert!(truck.get("Kind").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subclass_types_merge_into_existing_instances() {
+        let manifest = TestManifest::new()
+            .instance_step(
+                "fleet.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Vehicle" }),
+            )
+            .instance_step(
+                "vehicles.csv",
+                "SubClassInstanceStep",
+                json!({ "instanceType": "Vehicle", "subClassProperty": "Kind" }),
+            );
+        let files = [
+            ("fleet.csv", "Vehicle ID,Wheels\nV1,6\n"),
+            ("vehicles.csv", "Vehicle ID,Kind,Payload\nV1,Truck,12.5\n"),
+        ];
+        let run = import_with_vehicle_model(manifest, &files, true).await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let truck = run.instance("V1").unwrap();
+        assert_eq!(truck["@type"], json!(["Vehicle", "Truck"]));
+        assert_eq!(truck["Wheels"], json!([6]));
+        assert_eq!(truck["Payload"], json!(["12.5"]));
+    }
+
+    #[tokio::test]
+    async fn test_subclass_headers_are_validated() {
+        let vehicles = "Vehicle ID,Kind,Wheels,Horsepower\nV1,Truck,6,400\n";
+
+        let run = import_vehicles(vehicles, true).await;
+        assert!(run.has_error("Horsepower"));
+
+        let run = import_vehicles(vehicles, false).await;
+        assert!(run.has_warning("Horsepower"));
+        assert_eq!(run.instance("V1").unwrap()["Wheels"], json!([6]));
+    }
+
+    #[tokio::test]
+    async fn test_missing_subclass_reference() {
+        let vehicles = "Vehicle ID,Kind,Wheels\nV1,Truck,6\nV2,Boat,0\nV3,,4\n";
+
+        let run = import_vehicles(vehicles, true).await;
+        assert!(run.has_error("Subclass reference 'Boat' not found in vocabulary at row 2"));
+        assert!(run.has_error("Missing or empty subclass reference at row 3"));
+
+        let run = import_vehicles(vehicles, false).await;
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert_eq!(
+            run.instance("V2").unwrap()["@type"],
+            json!(["Vehicle", "Boat"])
+        );
+        assert!(run.instance("V3").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_properties_instance_values_are_typed() {
+        let model = model_csv(
+            &[],
+            "\
+Material,Material ID,Material,A material,Material ID,Identifier,@id,
+Material,Density,Material,A material,Density,Density in g/cm3,Decimal,
+Material,Grades,Material,A material,Grades,Quality grades,Integer,
+Material,Recyclable,Material,A material,Recyclable,Whether it can be recycled,Boolean,
+Material,Supplier,Material,A material,Supplier,Who supplies it,URI,Supplier
+Supplier,Supplier ID,Supplier,A supplier,Supplier ID,Identifier,@id,
+",
+        );
+        let manifest = TestManifest::new()
+            .instance_step(
+                "values.csv",
+                "PropertiesInstanceStep",
+                json!({ "instanceType": "Material" }),
+            )
+            .build();
+        let values = "\
+Material ID,Property ID,Property Value
+M1,Density,7.85
+M1,Grades,2;3
+M1,Recyclable,yes
+M1,Supplier,S1
+M1,Hardness,High
+";
+        let files = [("model.csv", model.as_str()), ("values.csv", values)];
+
+        let run = run_import(&manifest, &files, true).await;
+        assert!(run.has_error("Property ID 'Hardness' not found in vocabulary at row 5"));
+
+        let run = run_import(&manifest, &files, false).await;
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let material = run.instance("M1").unwrap();
+        assert_eq!(material["Density"], json!(["7.85"]));
+        assert_eq!(material["Grades"], json!([2, 3]));
+        assert_eq!(material["Recyclable"], json!([true]));
+        assert_eq!(material["Supplier"], json!(["S1"]));
+        assert_eq!(material["Hardness"], json!(["High"]));
+    }
+
+    #[tokio::test]
+    async fn test_unpivot_excludes_id_template_columns() {
+        let model = model_csv(
+            &[],
+            "\
+Reading,Temperature,Reading,A sensor reading,Temperature,Degrees Celsius,Decimal,
+Reading,Humidity,Reading,A sensor reading,Humidity,Relative humidity,Integer,
+",
+        );
+        let manifest = TestManifest::new().instance_step(
+            "readings.csv",
+            "UnpivotInstanceStep",
+            json!({ "instanceType": "Reading", "idTemplate": "{Site}-{Day}" }),
+        );
+        let readings = "Site,Day,Temperature,Humidity\nNorth,2024-05-01,21.5,40\n";
+        let run = run_import(
+            &manifest.build(),
+            &[("model.csv", &model), ("readings.csv", readings)],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let reading = run.instance("North-2024-05-01").unwrap();
+        assert_eq!(reading["Temperature"], json!(["21.5"]));
+        assert_eq!(reading["Humidity"], json!([40]));
+        assert!(reading.get("Site").is_none());
+        assert!(reading.get("Day").is_none());
+    }
+}
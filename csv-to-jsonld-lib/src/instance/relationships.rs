@@ -0,0 +1,268 @@
+use super::processor_impl::append_property_values;
+use super::types::InstanceProcessor;
+use crate::error::ProcessorError;
+use crate::excel::ExcelReader;
+use crate::manifest::ImportStep;
+use crate::types::{Header, IdOpt, JsonLdInstance};
+use crate::ProcessingState;
+use serde_json::{Map, Value as JsonValue};
+use std::mem::take;
+
+impl InstanceProcessor {
+    /// Link existing instances from an association table, where each row pairs a source
+    /// identifier with a target identifier
+    pub async fn process_relationship_instance(
+        &mut self,
+        step: &ImportStep,
+        s3_client: Option<&aws_sdk_s3::Client>,
+    ) -> Result<ProcessingState, ProcessorError> {
+        let class_type = step.instance_type.clone();
+
+        let sheet_or_path_name = step.id();
+
+        let (source_column, target_column, relationship_property) = match (
+            &step.source_column,
+            &step.target_column,
+            &step.relationship_property,
+        ) {
+            (Some(source), Some(target), Some(property)) => (source, target, property),
+            _ => {
+                return Err(ProcessorError::Processing(
+                    "RelationshipInstanceStep requires sourceColumn, targetColumn and relationshipProperty".into(),
+                ))
+            }
+        };
+
+        let relationship_header = self.relationship_header(relationship_property)?;
+        let inverse_header = step
+            .inverse_property
+            .as_ref()
+            .map(|inverse_property| self.relationship_header(inverse_property))
+            .transpose()?;
+
+        tracing::debug!("Reading relationship data from {:?}", &sheet_or_path_name);
+
+        let csv_bytes = if let Some(sheet_name) = &step.sheet {
+            // Excel processing
+            let excel_file = self.manifest.excel_file.as_ref().ok_or_else(|| {
+                ProcessorError::Processing("Excel file not specified in manifest".into())
+            })?;
+            let reader = excel_file.get_reader(s3_client).await.map_err(|e| {
+                tracing::error!("Failed to get Excel reader for {:#?}: {}", &excel_file, e);
+                ProcessorError::Processing(format!("Failed to get Excel reader: {}", e))
+            })?;
+
+            let mut excel_reader = ExcelReader::new(reader)?;
+            excel_reader.get_sheet_as_csv(sheet_name)?
+        } else {
+            // CSV processing
+            step.path.read_contents(s3_client).await?
+        };
+
+        let mut rdr = csv::Reader::from_reader(csv_bytes.as_slice());
+
+        let csv_headers: Vec<String> = rdr
+            .headers()
+            .map_err(|e| ProcessorError::Processing(format!("Failed to read CSV headers: {}", e)))?
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+
+        let column_index = |column: &str| {
+            csv_headers.iter().position(|h| h == column).ok_or_else(|| {
+                ProcessorError::Processing(format!(
+                    "Column '{}' not found in headers of CSV/sheet ({}): {:?}",
+                    column, &sheet_or_path_name, csv_headers
+                ))
+            })
+        };
+        let source_index = column_index(source_column)?;
+        let target_index = column_index(target_column)?;
+
+        for (result_row_num, result) in rdr.records().enumerate() {
+            let record = match result {
+                Ok(record) => record,
+                Err(e) => {
+                    let error =
+                        ProcessorError::Processing(format!("Failed to read CSV record: {}", e));
+                    if self.is_strict {
+                        self.processing_state.add_error_from(error);
+                    } else {
+                        self.processing_state.add_warning_from(error);
+                    }
+                    continue;
+                }
+            };
+
+            let source_value = record.get(source_index).unwrap_or("").trim();
+            let target_value = record.get(target_index).unwrap_or("").trim();
+            if source_value.is_empty() || target_value.is_empty() {
+                let error = ProcessorError::Processing(format!(
+                    "Row {} of {} is missing a value for '{}' or '{}'",
+                    result_row_num + 1,
+                    &sheet_or_path_name,
+                    source_column,
+                    target_column
+                ));
+                if self.is_strict {
+                    self.processing_state.add_error_from(error);
+                } else {
+                    self.processing_state.add_warning_from(error);
+                }
+                continue;
+            }
+
+            let source_id = self.namespaced_id(&class_type, source_value);
+            // Links are only made between loaded instances, so they are checked here rather than
+            // in the integrity pass
+            let recorded_references = self.references.len();
+            let target_id = match self.process_value(
+                target_value,
+                &relationship_header,
+                target_column,
+                target_column,
+                result_row_num,
+            ) {
+                Ok(JsonValue::String(target_id)) => target_id,
+                Ok(value) => value.to_string(),
+                Err(e) => {
+                    if self.is_strict {
+                        self.processing_state.add_error_from(e);
+                    } else {
+                        self.processing_state.add_warning_from(e);
+                    }
+                    continue;
+                }
+            };
+
+            self.references.truncate(recorded_references);
+
+            let missing: Vec<&str> = [&source_id, &target_id]
+                .into_iter()
+                .filter(|id| !self.instances.contains_key(*id))
+                .map(String::as_str)
+                .collect();
+            if !missing.is_empty() {
+                let error = ProcessorError::Processing(format!(
+                    "Row {} of {} links instances that do not exist: {:?}",
+                    result_row_num + 1,
+                    &sheet_or_path_name,
+                    missing
+                ));
+                if self.is_strict {
+                    self.processing_state.add_error_from(error);
+                } else {
+                    self.processing_state.add_warning_from(error);
+                }
+                continue;
+            }
+
+            let mut links = vec![(source_id.clone(), &relationship_header, target_id.clone())];
+            if let Some(inverse_header) = &inverse_header {
+                links.push((target_id, inverse_header, source_id));
+            }
+            for (id, header, linked_id) in links {
+                let mut properties = Map::new();
+                append_property_values(
+                    &mut properties,
+                    &header.name,
+                    vec![JsonValue::String(linked_id)],
+                );
+                let instance = JsonLdInstance {
+                    id: IdOpt::String(id.clone()),
+                    type_: vec![],
+                    properties,
+                };
+                if let Err(e) = self.update_or_insert_instance(instance) {
+                    let error = ProcessorError::Processing(format!(
+                        "Failed to update instance {}: {}",
+                        id, e
+                    ));
+                    if self.is_strict {
+                        self.processing_state.add_error_from(error);
+                    } else {
+                        self.processing_state.add_warning_from(error);
+                    }
+                }
+            }
+        }
+
+        Ok(take(&mut self.processing_state))
+    }
+
+    /// The header for a relationship property, looked up in the vocabulary
+    fn relationship_header(&self, property_ref: &str) -> Result<Header, ProcessorError> {
+        let property = self.find_property(property_ref).ok_or_else(|| {
+            ProcessorError::Processing(format!(
+                "Relationship property '{}' not found in vocabulary",
+                property_ref
+            ))
+        })?;
+        Header::try_from(property)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_relationship_links_and_inverse() {
+        let manifest = TestManifest::new()
+            .instance_step(
+                "products.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Product" }),
+            )
+            .instance_step(
+                "materials.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Material" }),
+            )
+            .instance_step(
+                "links.csv",
+                "RelationshipInstanceStep",
+                json!({
+                    "instanceType": "Product",
+                    "sourceColumn": "Product",
+                    "targetColumn": "Material",
+                    "relationshipProperty": "madeOf",
+                    "inverseProperty": "usedIn"
+                }),
+            )
+            .build();
+        let model = model_csv(
+            &[],
+            "\
+Product,Product ID,Product,A product,Product ID,Identifier,@id,
+Product,madeOf,Product,A product,Made Of,Materials used,URI,Material
+Material,Material ID,Material,A material,Material ID,Identifier,@id,
+Material,usedIn,Material,A material,Used In,Products using it,URI,Product
+",
+        );
+        let links = "Product,Material\nP1,M1\nP1,M2\nP2,M9\n";
+        let files = [
+            ("model.csv", model.as_str()),
+            ("products.csv", "Product ID\nP1\nP2\n"),
+            ("materials.csv", "Material ID\nM1\nM2\n"),
+            ("links.csv", links),
+        ];
+
+        let run = run_import(&manifest, &files, true).await;
+        assert!(run.has_error("Row 3 of links links instances that do not exist: [\"M9\"]"));
+
+        let run = run_import(&manifest, &files, false).await;
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let mut materials = run.instance("P1").unwrap()["Made Of"]
+            .as_array()
+            .unwrap()
+            .clone();
+        materials.sort_by_key(|m| m.to_string());
+        assert_eq!(materials, vec![json!("M1"), json!("M2")]);
+        assert_eq!(run.instance("M1").unwrap()["Used In"], json!(["P1"]));
+        assert_eq!(run.instance("M1").unwrap()["@type"], json!(["Material"]));
+        assert!(run.instance("P2").unwrap().get("Made Of").is_none());
+        assert!(run.instance("M9").is_none());
+    }
+}
@@ -0,0 +1,333 @@
+use super::processor_impl::{append_property_values, split_values};
+use super::types::InstanceProcessor;
+use crate::error::ProcessorError;
+use crate::manifest::ImportStep;
+use crate::types::{Header, IdOpt, JsonLdInstance, RepeatingGroup};
+use csv::StringRecord;
+use regex::Regex;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// A CSV column holding a property of one index of a repeating group
+#[derive(Debug, Clone)]
+pub(crate) struct RepeatingColumn {
+    pub group_index: usize,
+    pub index: u32,
+    pub header: Header,
+}
+
+/// The column patterns of a step's repeating groups, compiled once into regexes capturing the
+/// index, per group and column declaration
+pub(crate) struct RepeatingPatterns(Vec<Vec<Regex>>);
+
+impl RepeatingPatterns {
+    pub fn compile(groups: &[RepeatingGroup]) -> Result<Self, ProcessorError> {
+        let placeholder = regex::escape(RepeatingGroup::INDEX_PLACEHOLDER);
+        let patterns = groups
+            .iter()
+            .map(|group| {
+                group
+                    .columns
+                    .iter()
+                    .map(|column| {
+                        let escaped = regex::escape(&column.column).replace(&placeholder, r"(\d+)");
+                        Regex::new(&format!("^{}$", escaped)).map_err(|e| {
+                            ProcessorError::InvalidManifest(format!(
+                                "Invalid repeating group column '{}': {}",
+                                column.column, e
+                            ))
+                        })
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(patterns))
+    }
+
+    /// The repeating group, column declaration and index a header matches, if any
+    pub fn group_for_header(&self, header: &str) -> Option<(usize, usize, u32)> {
+        self.0
+            .iter()
+            .enumerate()
+            .find_map(|(group_index, patterns)| {
+                patterns
+                    .iter()
+                    .enumerate()
+                    .find_map(|(column_index, pattern)| {
+                        pattern
+                            .captures(header)
+                            .and_then(|captures| captures.get(1))
+                            .and_then(|index| index.as_str().parse().ok())
+                            .map(|index| (group_index, column_index, index))
+                    })
+            })
+    }
+}
+
+impl InstanceProcessor {
+    /// Match the columns of a step's repeating groups to properties of each group's class
+    pub(crate) fn validate_repeating_headers(
+        &mut self,
+        headers: &[String],
+        class_type: &str,
+        step: &ImportStep,
+        patterns: &RepeatingPatterns,
+    ) -> Result<Vec<Option<RepeatingColumn>>, ProcessorError> {
+        if step.repeating_groups.is_empty() {
+            return Ok(vec![None; headers.len()]);
+        }
+
+        let parent_labels = self.get_valid_property_labels(class_type, None, None)?;
+        let mut group_headers = Vec::with_capacity(step.repeating_groups.len());
+        let mut messages = Vec::new();
+        for group in &step.repeating_groups {
+            if !parent_labels
+                .iter()
+                .any(|l| l.name == group.new_relationship_property)
+            {
+                messages.push(format!(
+                    "Repeating group property '{}' is not a property of class '{}'",
+                    group.new_relationship_property, class_type
+                ));
+            }
+
+            let valid_labels = self.get_valid_property_labels(&group.instance_type, None, None)?;
+            let mut column_headers = Vec::with_capacity(group.columns.len());
+            for column in &group.columns {
+                let property_label = self
+                    .find_property(&column.map_to)
+                    .and_then(|p| p.label.clone());
+                let header = property_label.and_then(|property_label| {
+                    valid_labels
+                        .iter()
+                        .find(|label| label.name == property_label)
+                        .cloned()
+                });
+                if header.is_none() {
+                    messages.push(format!(
+                        "Repeating group column '{}' maps to '{}', which is not a property of class '{}'",
+                        column.column, column.map_to, group.instance_type
+                    ));
+                }
+                column_headers.push(header);
+            }
+            group_headers.push(column_headers);
+        }
+
+        let columns: Vec<Option<RepeatingColumn>> = headers
+            .iter()
+            .map(|header| {
+                let (group_index, column_index, index) = patterns.group_for_header(header)?;
+                group_headers[group_index][column_index]
+                    .clone()
+                    .map(|header| RepeatingColumn {
+                        group_index,
+                        index,
+                        header,
+                    })
+            })
+            .collect();
+
+        for (group_index, group) in step.repeating_groups.iter().enumerate() {
+            if !columns
+                .iter()
+                .flatten()
+                .any(|column| column.group_index == group_index)
+            {
+                messages.push(format!(
+                    "No columns found in CSV for repeating group '{}'",
+                    group.new_relationship_property
+                ));
+            }
+        }
+
+        for message in messages {
+            if self.is_strict {
+                return Err(ProcessorError::Processing(message));
+            } else {
+                self.processing_state
+                    .add_warning(message, Some("header_validation".to_string()));
+            }
+        }
+
+        Ok(columns)
+    }
+
+    /// Create one instance per populated index of each repeating group in the row, returning the
+    /// relationship property and reference used to link each of them from the parent
+    pub(crate) fn repeating_group_instances(
+        &mut self,
+        step: &ImportStep,
+        columns: &[Option<RepeatingColumn>],
+        headers: &[String],
+        record: &StringRecord,
+        parent_id: &str,
+        row_num: usize,
+    ) -> Vec<(String, JsonValue)> {
+        let mut groups: BTreeMap<(usize, u32), Map<String, JsonValue>> = BTreeMap::new();
+        for (i, column) in columns.iter().enumerate() {
+            let Some(column) = column else {
+                continue;
+            };
+            let Some(value) = record.get(i).filter(|value| !value.trim().is_empty()) else {
+                continue;
+            };
+            let mut values = vec![];
+            for value in split_values(step, &column.header.datatype, value) {
+                match self.process_value(value, &column.header, &headers[i], &headers[i], row_num) {
+                    Ok(value) => values.push(value),
+                    Err(e) => {
+                        if self.is_strict {
+                            self.processing_state.add_error_from(e);
+                        } else {
+                            self.processing_state.add_warning_from(e);
+                        }
+                    }
+                }
+            }
+            if !values.is_empty() {
+                append_property_values(
+                    groups
+                        .entry((column.group_index, column.index))
+                        .or_default(),
+                    &column.header.name,
+                    values,
+                );
+            }
+        }
+
+        let mut references = vec![];
+        for ((group_index, index), properties) in groups {
+            let group = &step.repeating_groups[group_index];
+            let index = index.to_string();
+            let id = match group.id_template_for(&index) {
+                Some(id_template) => {
+                    match self.row_identifier(Some(&id_template), None, headers, record, row_num) {
+                        Ok(id) => self.namespaced_id(&group.instance_type, &id),
+                        Err(e) => {
+                            if self.is_strict {
+                                self.processing_state.add_error_from(e);
+                            } else {
+                                self.processing_state.add_warning_from(e);
+                            }
+                            continue;
+                        }
+                    }
+                }
+                None => {
+                    let name = [
+                        self.instances_base_iri.as_str(),
+                        parent_id,
+                        group.new_relationship_property.as_str(),
+                        group.instance_type.as_str(),
+                        index.as_str(),
+                    ]
+                    .join("\u{1f}");
+                    Uuid::new_v5(&Uuid::NAMESPACE_URL, name.as_bytes()).to_string()
+                }
+            };
+
+            let instance = JsonLdInstance {
+                id: IdOpt::String(id.clone()),
+                type_: vec![IdOpt::String(group.instance_type.clone())],
+                properties,
+            };
+            if let Err(e) = self.update_or_insert_instance(instance) {
+                if self.is_strict {
+                    self.processing_state.add_error_from(e);
+                } else {
+                    self.processing_state.add_warning_from(e);
+                }
+                continue;
+            }
+
+            let mut reference = Map::new();
+            reference.insert("@id".to_string(), JsonValue::String(id));
+            references.push((
+                group.new_relationship_property.clone(),
+                JsonValue::Object(reference),
+            ));
+        }
+
+        references
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::{json, Value as JsonValue};
+
+    fn model() -> String {
+        model_csv(
+            &[],
+            "\
+BillOfMaterials,BOM ID,Bill of Materials,A bill of materials,BOM ID,Identifier,@id,
+BillOfMaterials,Lines,Bill of Materials,A bill of materials,Lines,Line items,URI,Line
+Line,Material,Line,A line item,Material,Material name,String,
+Line,Quantity,Line,A line item,Quantity,Quantity used,Integer,
+",
+        )
+    }
+
+    const BOMS: &str = "\
+BOM ID,Item 1 Material,Item 1 Qty,Item 2 Material,Item 2 Qty,Item 3 Material,Item 3 Qty
+B1,Steel,4,,,Copper,2
+";
+
+    /// A manifest importing `boms.csv` with one repeating group of lines, with extra group `fields`
+    fn manifest(mut fields: JsonValue) -> String {
+        fields["instanceType"] = json!("Line");
+        fields["newRelationshipProperty"] = json!("Lines");
+        fields["columns"] = json!([
+            { "column": "Item {i} Material", "mapTo": "Material" },
+            { "column": "Item {i} Qty", "mapTo": "Quantity" }
+        ]);
+        TestManifest::new()
+            .instance_step(
+                "boms.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "BillOfMaterials", "repeatingGroups": [fields] }),
+            )
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_repeating_groups_skip_empty_indexes() {
+        let manifest = manifest(json!({ "idTemplate": "{BOM ID}-{i}" }));
+        let run = run_import(
+            &manifest,
+            &[("model.csv", &model()), ("boms.csv", BOMS)],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert_eq!(
+            run.instance("B1").unwrap()["Lines"],
+            json!([{ "@id": "B1-1" }, { "@id": "B1-3" }])
+        );
+        let steel = run.instance("B1-1").unwrap();
+        assert_eq!(steel["@type"], json!(["Line"]));
+        assert_eq!(steel["Material"], json!(["Steel"]));
+        assert_eq!(steel["Quantity"], json!([4]));
+        assert!(run.instance("B1-2").is_none());
+        assert_eq!(run.instance("B1-3").unwrap()["Material"], json!(["Copper"]));
+    }
+
+    #[tokio::test]
+    async fn test_repeating_group_ids_are_stable_without_template() {
+        let manifest = manifest(json!({}));
+        let model = model();
+        let files = [("model.csv", model.as_str()), ("boms.csv", BOMS)];
+        let first = run_import(&manifest, &files, true).await;
+        let second = run_import(&manifest, &files, true).await;
+
+        assert!(first.errors.is_empty(), "{:?}", first.errors);
+        let lines = &first.instance("B1").unwrap()["Lines"];
+        assert_eq!(lines.as_array().unwrap().len(), 2);
+        assert_ne!(lines[0], lines[1]);
+        assert_eq!(*lines, second.instance("B1").unwrap()["Lines"]);
+    }
+}
@@ -32,6 +32,23 @@ impl InstanceSerializer {
             "rdfs".to_string(),
             serde_json::Value::String("http://www.w3.org/2000/01/rdf-schema#".to_string()),
         );
+        if vocabulary
+            .properties
+            .values()
+            .any(|prop| prop.unit.is_some())
+        {
+            context.insert(
+                "qudt".to_string(),
+                serde_json::Value::String("http://qudt.org/schema/qudt/".to_string()),
+            );
+        }
+
+        // Add the manifest's prefixes
+        for (prefix, namespace) in &self.manifest.prefixes {
+            context
+                .entry(prefix.clone())
+                .or_insert_with(|| serde_json::Value::String(namespace.clone()));
+        }
 
         // Add model baseIRI for term resolution
         if !self.base_iri.is_empty() {
@@ -66,21 +83,24 @@ impl InstanceSerializer {
             };
             property_context.insert("@id".to_string(), serde_json::Value::String(property_iri));
 
-            // If range is a class (not xsd:*), mark as @id type
-            if let Some(range) = &prop.range {
-                if range.iter().any(|r| {
-                    matches!(r, PropertyDatatype::URI(_))
-                        || matches!(r, PropertyDatatype::Picklist(_))
-                }) {
-                    property_context.insert(
-                        "@type".to_string(),
-                        serde_json::Value::String("@id".to_string()),
-                    );
-                } else if !range.is_empty() {
-                    property_context.insert(
-                        "@type".to_string(),
-                        serde_json::to_value(range[0].clone()).unwrap(),
-                    );
+            // If range is a class (not xsd:*), mark as @id type. Values with a unit are quantity
+            // nodes, so they are not coerced
+            if prop.unit.is_none() {
+                if let Some(range) = &prop.range {
+                    if range.iter().any(|r| {
+                        matches!(r, PropertyDatatype::URI(_))
+                            || matches!(r, PropertyDatatype::Picklist(_))
+                    }) {
+                        property_context.insert(
+                            "@type".to_string(),
+                            serde_json::Value::String("@id".to_string()),
+                        );
+                    } else if !range.is_empty() && range[0] != PropertyDatatype::LangString {
+                        property_context.insert(
+                            "@type".to_string(),
+                            serde_json::to_value(range[0].clone()).unwrap(),
+                        );
+                    }
                 }
             }
 
@@ -1,7 +1,10 @@
+use super::integrity::InstanceReference;
 use crate::error::ProcessingState;
-use crate::types::{JsonLdInstance, VocabularyMap};
+use crate::manifest::ImportStep;
+use crate::types::{JsonLdInstance, ParseProfile, VocabularyMap};
 use crate::Manifest;
-use std::collections::HashMap;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 pub struct InstanceProcessor {
@@ -14,6 +17,14 @@ pub struct InstanceProcessor {
     pub(crate) model_base_iri: String,
     pub(crate) instances_base_iri: String,
     pub(crate) processing_state: ProcessingState,
+    pub(crate) current_source: String,
+    pub(crate) references: Vec<InstanceReference>,
+    /// Sources and rows each instance was loaded from, in load order
+    pub(crate) instance_origins: HashMap<String, Vec<(String, usize)>>,
+    pub(crate) parse_profile: ParseProfile,
+    pub(crate) column_parse_profiles: HashMap<String, ParseProfile>,
+    pub(crate) ambiguous_date_columns: HashSet<String>,
+    pub(crate) compiled_patterns: HashMap<String, Regex>,
 }
 
 impl InstanceProcessor {
@@ -45,9 +56,33 @@ impl InstanceProcessor {
             model_base_iri,
             instances_base_iri,
             processing_state: ProcessingState::new(),
+            current_source: String::new(),
+            references: Vec::new(),
+            instance_origins: HashMap::new(),
+            parse_profile: ParseProfile::default(),
+            column_parse_profiles: HashMap::new(),
+            ambiguous_date_columns: HashSet::new(),
+            compiled_patterns: HashMap::new(),
         }
     }
 
+    /// Set up the per-step state used while processing values: the source name reported with
+    /// references, and the parse profiles resolved for the step and each of its columns
+    pub(crate) fn begin_step(&mut self, step: &ImportStep) {
+        self.current_source = step.id();
+        self.parse_profile = step
+            .parse_profile
+            .as_ref()
+            .map(|profile| profile.merged_over(&self.manifest.parse_profile))
+            .unwrap_or_else(|| self.manifest.parse_profile.clone());
+        self.column_parse_profiles = step
+            .column_parse_profiles
+            .iter()
+            .map(|(column, profile)| (column.clone(), profile.merged_over(&self.parse_profile)))
+            .collect();
+        self.ambiguous_date_columns.clear();
+    }
+
     pub fn set_vocabulary(&mut self, vocabulary: VocabularyMap) {
         self.vocabulary = Some(vocabulary);
     }
@@ -0,0 +1,148 @@
+use super::types::InstanceProcessor;
+use crate::error::ProcessorError;
+use crate::types::PropertyDatatype;
+use crate::utils::{canonical_decimal, resolve_unit, split_unit};
+use serde_json::{json, Value as JsonValue};
+
+impl InstanceProcessor {
+    /// Read a value of a property with a declared unit as a QUDT quantity value. A unit written
+    /// with the value (e.g. "$22.60", "12 kg") must match the declared one
+    pub(crate) fn process_quantity(
+        &mut self,
+        value: &str,
+        datatype: &PropertyDatatype,
+        unit: &str,
+        column: &str,
+        header_name: &str,
+        row_num: usize,
+    ) -> Result<JsonValue, ProcessorError> {
+        let Some((number, written_unit)) = split_unit(value) else {
+            return Err(ProcessorError::Processing(format!(
+                "[Column: {}, Row: {}], Value '{}' has a unit on both sides of its number",
+                header_name,
+                row_num + 1,
+                value
+            )));
+        };
+        if let Some(written_unit) = written_unit {
+            if resolve_unit(written_unit).as_deref() != Some(unit) {
+                return Err(ProcessorError::Processing(format!(
+                    "[Column: {}, Row: {}], Unit '{}' of value '{}' does not match the property's unit <{}>",
+                    header_name,
+                    row_num + 1,
+                    written_unit,
+                    value,
+                    unit
+                )));
+            }
+        }
+
+        let numeric_value =
+            match self.process_typed_value(number, datatype, column, header_name, row_num)? {
+                JsonValue::String(decimal)
+                    if canonical_decimal(&decimal).as_deref() == Some(decimal.as_str()) =>
+                {
+                    json!({ "@value": decimal, "@type": "xsd:decimal" })
+                }
+                JsonValue::Number(number) => JsonValue::Number(number),
+                // Invalid numbers have already been reported and are kept as they are
+                raw_value => return Ok(raw_value),
+            };
+
+        Ok(json!({
+            "@type": "qudt:QuantityValue",
+            "qudt:numericValue": numeric_value,
+            "qudt:unit": { "@id": unit },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, ImportRun, TestManifest};
+    use serde_json::json;
+
+    async fn import_products(products: &str, is_strict: bool) -> ImportRun {
+        let manifest = TestManifest::new().instance_step(
+            "products.csv",
+            "BasicInstanceStep",
+            json!({ "instanceType": "Product" }),
+        );
+        let model = model_csv(
+            &["Unit"],
+            "\
+Product,Product ID,Product,A product,Product ID,Identifier,@id,,
+Product,Price,Product,A product,Price,List price,Decimal,,USD
+Product,Weight,Product,A product,Weight,Shipping weight,Decimal,,kg
+Product,Recycled,Product,A product,Recycled,Recycled content,Decimal,,%
+Product,Discount,Product,A product,Discount,Discount rate,Decimal,,
+",
+        );
+        run_import(
+            &manifest.build(),
+            &[("model.csv", &model), ("products.csv", products)],
+            is_strict,
+        )
+        .await
+    }
+
+    fn quantity(value: &str, unit: &str) -> serde_json::Value {
+        json!([{
+            "@type": "qudt:QuantityValue",
+            "qudt:numericValue": { "@value": value, "@type": "xsd:decimal" },
+            "qudt:unit": { "@id": unit }
+        }])
+    }
+
+    #[tokio::test]
+    async fn test_quantities() {
+        let products = "\
+Product ID,Price,Weight,Recycled,Discount
+P1,$22.60,12 kg,34%,34%
+P2,\"1,305.00\",0.5,,
+";
+        let run = import_products(products, true).await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let p1 = run.instance("P1").unwrap();
+        assert_eq!(
+            p1["Price"],
+            quantity("22.60", "http://qudt.org/vocab/currency/USD")
+        );
+        assert_eq!(
+            p1["Weight"],
+            quantity("12", "http://qudt.org/vocab/unit/KiloGM")
+        );
+        // A percent unit keeps the number as written, without a unit it becomes a fraction
+        assert_eq!(
+            p1["Recycled"],
+            quantity("34", "http://qudt.org/vocab/unit/PERCENT")
+        );
+        assert_eq!(p1["Discount"], json!(["0.34"]));
+
+        let p2 = run.instance("P2").unwrap();
+        assert_eq!(
+            p2["Price"],
+            quantity("1305.00", "http://qudt.org/vocab/currency/USD")
+        );
+
+        // Quantity values are nodes, so their terms are not coerced to a datatype
+        let context = &run.instances["@context"];
+        assert!(context["Price"].get("@type").is_none());
+        assert_eq!(context["Discount"]["@type"], json!("xsd:decimal"));
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_units() {
+        let products = "Product ID,Price,Weight\nP1,€22.60,5 lb\n";
+        let run = import_products(products, false).await;
+
+        assert!(run.has_warning(
+            "[Column: Price, Row: 1], Unit '€' of value '€22.60' does not match the property's unit <http://qudt.org/vocab/currency/USD>"
+        ));
+        assert!(run.has_warning("[Column: Weight, Row: 1], Unit 'lb' of value '5 lb'"));
+        let p1 = run.instance("P1").unwrap();
+        assert!(p1.get("Price").is_none());
+        assert!(p1.get("Weight").is_none());
+    }
+}
@@ -1,7 +1,13 @@
+use super::extraction::is_extracted_column;
+use super::nested::nested_for_header;
+use super::repeating::RepeatingPatterns;
 use super::types::InstanceProcessor;
-use crate::error::ProcessorError;
-use crate::types::{Header, IdOpt, PivotColumn, PropertyDatatype};
-use crate::utils::expand_iri_with_base;
+use crate::error::{ProcessingState, ProcessorError};
+use crate::manifest::ImportStep;
+use crate::types::{
+    ColumnOverride, Header, IdOpt, PivotColumn, PropertyDatatype, ValueConstraints, VocabularyTerm,
+};
+use crate::utils::{expand_iri_with_base, expand_prefixed_name, is_language_tag};
 use std::collections::HashSet;
 
 impl InstanceProcessor {
@@ -9,12 +15,22 @@ impl InstanceProcessor {
         &mut self,
         headers: &[String],
         class_type: &str,
-        identifier_label: &str,
-        pivot_columns: Option<&Vec<PivotColumn>>,
-        map_to_label: Option<&String>,
+        identifier_label: Option<&str>,
+        step: &ImportStep,
+        repeating_patterns: &RepeatingPatterns,
     ) -> Result<Vec<Option<Header>>, ProcessorError> {
-        let valid_labels =
-            self.get_valid_property_labels(class_type, pivot_columns, map_to_label)?;
+        let mut valid_labels = self.get_valid_property_labels(
+            class_type,
+            step.pivot_columns.as_ref(),
+            step.map_to_label.as_ref(),
+        )?;
+
+        // Rows of a subclass step may carry properties of the parent class or any of its subclasses
+        if step.sub_class_property.is_some() {
+            for subclass_type in self.subclass_types(class_type) {
+                valid_labels.extend(self.get_valid_property_labels(&subclass_type, None, None)?);
+            }
+        }
 
         let mut unknown_headers = Vec::new();
         let ignorable_headers = match self.ignore.get(class_type) {
@@ -36,17 +52,60 @@ impl InstanceProcessor {
             }
 
             // Skip if it's the identifier column
-            if header == identifier_label {
+            if Some(header.as_str()) == identifier_label {
                 let final_header = Header {
                     name: header.clone(),
                     datatype: PropertyDatatype::ID,
                     is_label_header: false,
+                    unit: None,
+                    language: None,
+                    constraints: ValueConstraints::default(),
                 };
                 final_headers.push(Some(final_header));
                 continue;
             }
 
-            let final_header_candidate = valid_labels.iter().find(|label| &label.name == header);
+            // Extra item, subclass, nested object, repeating group and extracted entity columns are
+            // handled by the step, not written as properties
+            if step.extra_items.iter().any(|item| &item.column == header)
+                || step.sub_class_property.as_ref() == Some(header)
+                || nested_for_header(&step.nested, header).is_some()
+                || repeating_patterns.group_for_header(header).is_some()
+                || is_extracted_column(&step.extract_entities, header)
+            {
+                final_headers.push(None);
+                continue;
+            }
+
+            // Columns mapped onto a vocabulary property through an override take that property's
+            // label, so their values are serialized under the property's context term
+            if let Some(over_ride) = step
+                .overrides
+                .iter()
+                .find(|o| &o.column == header && o.map_to != "@id" && !o.map_to.starts_with('$'))
+            {
+                match self.resolve_override_header(over_ride, &valid_labels) {
+                    Some(final_header) => {
+                        let language = step.column_languages.get(header).cloned();
+                        final_headers.push(Some(with_language(
+                            final_header,
+                            language,
+                            &mut self.processing_state,
+                        )))
+                    }
+                    None => {
+                        unknown_headers.push(format!("{} (mapTo: {})", header, over_ride.map_to));
+                        final_headers.push(None);
+                    }
+                }
+                continue;
+            }
+
+            let (label_name, language) = column_language(step, header, &valid_labels);
+            let final_header_candidate = valid_labels
+                .iter()
+                .find(|label| label.name == label_name)
+                .map(|label| with_language(label.clone(), language, &mut self.processing_state));
 
             if final_header_candidate.is_none() && !ignorable_headers.contains(header) {
                 tracing::debug!(
@@ -56,8 +115,9 @@ impl InstanceProcessor {
                 );
                 tracing::debug!("Valid labels: {:#?}", valid_labels);
                 unknown_headers.push(header.clone());
+                final_headers.push(None);
             } else if final_header_candidate.is_some() {
-                final_headers.push(Some(final_header_candidate.unwrap().clone()));
+                final_headers.push(final_header_candidate);
             } else if ignorable_headers.contains(header) {
                 final_headers.push(None);
             }
@@ -80,6 +140,70 @@ impl InstanceProcessor {
         Ok(final_headers)
     }
 
+    /// The IRI a class or property reference resolves to: a prefixed name expanded, or a name
+    /// under the model base IRI
+    pub(crate) fn model_iri(&self, reference: &str) -> String {
+        expand_prefixed_name(&self.manifest.prefixes, reference)
+            .unwrap_or_else(|| expand_iri_with_base(&self.model_base_iri, reference))
+    }
+
+    /// IRIs of the vocabulary classes declared as subclasses of the given class
+    fn subclass_types(&self, class_type: &str) -> Vec<String> {
+        let Some(vocab) = self.vocabulary.as_ref() else {
+            return vec![];
+        };
+        let class_iri = self.model_iri(class_type);
+        vocab
+            .classes
+            .values()
+            .filter(|class| {
+                class
+                    .sub_class_of
+                    .iter()
+                    .flatten()
+                    .any(|parent| parent == class_type || self.model_iri(parent) == class_iri)
+            })
+            .map(|class| class.id.final_iri())
+            .collect()
+    }
+
+    /// Look up a vocabulary property by its ID as written in the model (original or replaced),
+    /// its label, or its IRI
+    pub(crate) fn find_property(&self, property_ref: &str) -> Option<&VocabularyTerm> {
+        let vocab = self.vocabulary.as_ref()?;
+        let property_iri = self.model_iri(property_ref);
+        vocab
+            .properties
+            .iter()
+            .find(|(id, property)| {
+                let matches_id = match id {
+                    IdOpt::String(string_id) => string_id == property_ref,
+                    IdOpt::ReplacementMap { original_id, .. } => original_id == property_ref,
+                };
+                matches_id
+                    || property.label.as_deref() == Some(property_ref)
+                    || property.id.final_iri() == property_ref
+                    || property.id.final_iri() == property_iri
+            })
+            .map(|(_, property)| property)
+    }
+
+    /// Find the header for the vocabulary property an override maps a column to, matching the
+    /// property by label or IRI. Only properties valid for the class being processed are returned
+    fn resolve_override_header(
+        &self,
+        over_ride: &ColumnOverride,
+        valid_labels: &HashSet<Header>,
+    ) -> Option<Header> {
+        let property_label = self
+            .find_property(&over_ride.map_to)
+            .and_then(|p| p.label.as_ref())?;
+        valid_labels
+            .iter()
+            .find(|label| &label.name == property_label)
+            .cloned()
+    }
+
     pub(crate) fn get_valid_property_labels(
         &mut self,
         class_type: &str,
@@ -87,8 +211,8 @@ impl InstanceProcessor {
         map_to_label: Option<&String>,
     ) -> Result<HashSet<Header>, ProcessorError> {
         let mut valid_labels: HashSet<Option<Header>> = HashSet::new();
+        let class_iri = self.model_iri(class_type);
         let vocab = self.vocabulary.as_mut().unwrap();
-        let class_iri = expand_iri_with_base(&self.model_base_iri, class_type);
 
         if let Some(pivot_columns) = pivot_columns {
             for pivot_column in pivot_columns {
@@ -141,14 +265,20 @@ impl InstanceProcessor {
         let mut valid_labels: HashSet<Header> = valid_labels.into_iter().flatten().collect();
 
         if let Some(map_to_label) = map_to_label {
-            let mut map_to_label_header = valid_labels.take(&Header {
-                name: map_to_label.clone(),
-                ..Default::default()
-            });
-            match map_to_label_header.as_mut() {
-                Some(header) => {
+            // Matched by name and datatype only, since the header may also carry a unit,
+            // language or value constraints
+            let map_to_label_header = valid_labels
+                .iter()
+                .find(|header| {
+                    &header.name == map_to_label
+                        && matches!(header.datatype, PropertyDatatype::String)
+                })
+                .cloned();
+            match map_to_label_header {
+                Some(mut header) => {
+                    valid_labels.remove(&header);
                     header.set_is_label_header(true);
-                    valid_labels.insert(header.clone());
+                    valid_labels.insert(header);
                 }
                 None => {
                     let message = format!(
@@ -215,7 +345,9 @@ impl InstanceProcessor {
                         }) && prop.label == Some(pivot_column_ref_property.to_string())
                             && prop.range.is_some()
                             && prop.range.clone().unwrap().iter().any(|r| match r {
-                                PropertyDatatype::URI(Some(iri)) => pivot_class.clone() == *iri,
+                                PropertyDatatype::URI(Some(iri)) => {
+                                    pivot_class_iri.final_iri() == *iri
+                                }
                                 _ => false,
                             })
                     });
@@ -317,3 +449,190 @@ impl InstanceProcessor {
         Ok(())
     }
 }
+
+/// The property label a column matches and the language of its values, taken from the step's
+/// columnLanguages or from a "Name@de" header suffix
+fn column_language(
+    step: &ImportStep,
+    header: &str,
+    valid_labels: &HashSet<Header>,
+) -> (String, Option<String>) {
+    if let Some(language) = step.column_languages.get(header) {
+        return (header.to_string(), Some(language.clone()));
+    }
+    match header.rsplit_once('@') {
+        Some((label, language))
+            if is_language_tag(language)
+                && valid_labels.iter().any(|valid| valid.name == label.trim()) =>
+        {
+            (label.trim().to_string(), Some(language.to_string()))
+        }
+        _ => (header.to_string(), None),
+    }
+}
+
+/// Attach a column's language to its header. Only string properties take a language
+fn with_language(
+    mut header: Header,
+    language: Option<String>,
+    processing_state: &mut ProcessingState,
+) -> Header {
+    let Some(language) = language else {
+        return header;
+    };
+    if matches!(
+        header.datatype,
+        PropertyDatatype::String | PropertyDatatype::LangString
+    ) {
+        header.language = Some(language);
+    } else {
+        processing_state.add_warning(
+            format!(
+                "Language '{}' ignored for non-string property '{}'",
+                language, header.name
+            ),
+            Some("header_validation".to_string()),
+        );
+    }
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::{json, Value as JsonValue};
+
+    fn model() -> String {
+        model_csv(
+            &[],
+            "\
+Product,Product ID,Product,A product,Product ID,Identifier,@id,
+Product,Product Name,Product,A product,Product Name,Display name,String,
+Product,Weight,Product,A product,Weight,Weight in kg,Decimal,
+",
+        )
+    }
+
+    /// A manifest importing `products.csv` as Products, with extra step `fields`
+    fn manifest(mut fields: JsonValue) -> String {
+        fields["instanceType"] = json!("Product");
+        TestManifest::new()
+            .instance_step("products.csv", "BasicInstanceStep", fields)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_overrides_map_columns_by_label_and_iri() {
+        let manifest = manifest(json!({ "overrides": [
+            { "column": "Nom", "mapTo": "Product Name" },
+            { "column": "Title", "mapTo": "Product Name" },
+            { "column": "Poids", "mapTo": "http://example.com/terms/weight" }
+        ] }));
+        let products = "Product ID,Nom,Title,Poids\nP1,Vis,Screw,0.25\n";
+        let run = run_import(
+            &manifest,
+            &[("model.csv", &model()), ("products.csv", products)],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let product = run.instance("P1").unwrap();
+        // Both columns mapped to the same property contribute values
+        assert_eq!(product["Product Name"], json!(["Vis", "Screw"]));
+        assert_eq!(product["Weight"], json!(["0.25"]));
+    }
+
+    #[tokio::test]
+    async fn test_override_to_unknown_property() {
+        let manifest = manifest(json!({
+            "overrides": [{ "column": "Colour", "mapTo": "Color" }]
+        }));
+        let products = "Product ID,Colour\nP1,Red\n";
+        let model = model();
+        let files = [("model.csv", model.as_str()), ("products.csv", products)];
+
+        let run = run_import(&manifest, &files, true).await;
+        assert!(run.has_error("Colour (mapTo: Color)"));
+
+        let run = run_import(&manifest, &files, false).await;
+        assert!(run.has_warning("Colour (mapTo: Color)"));
+        assert!(run.instance("P1").unwrap().get("Colour").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_vocabulary_identifier_takes_precedence_over_id_override() {
+        let manifest = manifest(json!({
+            "overrides": [{ "column": "Code", "mapTo": "@id" }]
+        }));
+        let products = "Product ID,Code,Product Name\nP1,C-1,Screw\n";
+        let run = run_import(
+            &manifest,
+            &[("model.csv", &model()), ("products.csv", products)],
+            false,
+        )
+        .await;
+
+        assert!(run.instance("P1").is_some());
+        assert!(run.instance("C-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_language_tagged_columns_merge() {
+        let manifest = manifest(json!({
+            "columnLanguages": { "Titel": "de" },
+            "overrides": [{ "column": "Titel", "mapTo": "Product Name" }]
+        }));
+        let products = "\
+Product ID,Product Name,Product Name@ja,Titel,Weight@de
+P1,Screw,ネジ,Schraube,0.25
+";
+        let run = run_import(
+            &manifest,
+            &[("model.csv", &model()), ("products.csv", products)],
+            false,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let product = run.instance("P1").unwrap();
+        assert_eq!(
+            product["Product Name"],
+            json!([
+                "Screw",
+                { "@value": "ネジ", "@language": "ja" },
+                { "@value": "Schraube", "@language": "de" }
+            ])
+        );
+        // Languages only apply to string properties
+        assert!(run.has_warning("Language 'de' ignored for non-string property 'Weight'"));
+        assert_eq!(product["Weight"], json!(["0.25"]));
+    }
+
+    #[tokio::test]
+    async fn test_map_to_label_with_constrained_property() {
+        let model = model_csv(
+            &["Pattern", "Max Length"],
+            "\
+Product,Product ID,Product,A product,Product ID,Identifier,@id,,,
+Product,Product Name,Product,A product,Product Name,Display name,String,,^[A-Z],20
+",
+        );
+        let manifest = manifest(json!({ "mapToLabel": "Product Name" }));
+        let products = "Product ID,Product Name\nP1,Screw\nP2,washer\n";
+        let files = [("model.csv", model.as_str()), ("products.csv", products)];
+
+        let run = run_import(&manifest, &files, false).await;
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert!(!run.has_warning("mapToLabel"), "{:?}", run.warnings);
+        let product = run.instance("P1").unwrap();
+        assert_eq!(product["Product Name"], json!(["Screw"]));
+        assert_eq!(product["label"], json!(["Screw"]));
+        // The label column's constraints still apply
+        assert!(run.has_warning("washer"));
+
+        let run = run_import(&manifest, &files, true).await;
+        assert!(!run.has_error("mapToLabel"));
+        assert!(run.has_error("washer"));
+    }
+}
@@ -1,69 +1,67 @@
 use super::types::InstanceProcessor;
 use crate::error::ProcessorError;
-use crate::types::{IdOpt, PropertyDatatype};
-use crate::utils::{expand_iri_with_base, to_kebab_case, DATE_FORMATS};
-use serde_json::Value as JsonValue;
+use crate::types::{Header, IdOpt, ParseProfile, PropertyDatatype};
+use crate::utils::{
+    canonical_decimal, expand_iri_with_base, is_valid_url, percent_to_fraction, to_kebab_case,
+    TIME_FORMATS,
+};
+use chrono::{DateTime, FixedOffset, SecondsFormat, TimeZone, Utc};
+use regex::Regex;
+use serde_json::{json, Value as JsonValue};
+use std::sync::OnceLock;
 
 impl InstanceProcessor {
+    /// Process a value from a CSV column. `column` selects the column's parse profile, while
+    /// `header_name` names the value in messages
     pub(crate) fn process_value(
+        &mut self,
+        value: &str,
+        header: &Header,
+        column: &str,
+        header_name: &str,
+        row_num: usize,
+    ) -> Result<JsonValue, ProcessorError> {
+        let processed_value = match header.unit.as_deref() {
+            Some(unit) => {
+                self.process_quantity(value, &header.datatype, unit, column, header_name, row_num)?
+            }
+            None => {
+                self.process_typed_value(value, &header.datatype, column, header_name, row_num)?
+            }
+        };
+        self.check_constraints(
+            value,
+            &processed_value,
+            &header.datatype,
+            &header.constraints,
+            header_name,
+            row_num,
+        )?;
+        Ok(processed_value)
+    }
+
+    /// Read a value as its property's datatype
+    pub(crate) fn process_typed_value(
         &mut self,
         value: &str,
         datatype: &PropertyDatatype,
+        column: &str,
         header_name: &str,
         row_num: usize,
     ) -> Result<JsonValue, ProcessorError> {
         match datatype {
             PropertyDatatype::ID => Ok(JsonValue::String(value.to_string())),
             PropertyDatatype::Date => {
+                let profile = self.parse_profile_for(column).clone();
                 let trimmed_value = value.trim();
-                let date_result = DATE_FORMATS
-                    .iter()
-                    .find_map(|fmt| {
-                        // First try exact parsing
-                        if let Ok(date) = chrono::NaiveDate::parse_from_str(trimmed_value, fmt) {
-                            return Some(date);
-                        }
-
-                        // Handle partial dates
-                        match *fmt {
-                            // Year only - default to Jan 1
-                            "%Y" => {
-                                trimmed_value.parse::<i32>().ok().and_then(|year| {
-                                    chrono::NaiveDate::from_ymd_opt(
-                                        year, 1, // January
-                                        1, // 1st
-                                    )
-                                })
-                            }
-                            // Year-month formats - default to 1st of month
-                            "%Y-%m" | "%Y/%m" | "%b %Y" | "%B %Y" | "%m-%Y" => {
-                                if let Ok(parsed) = chrono::NaiveDate::parse_from_str(
-                                    &format!("{}-01", trimmed_value.replace("/", "-")),
-                                    "%Y-%m-%d",
-                                ) {
-                                    Some(parsed)
-                                } else if let Ok(parsed) = chrono::NaiveDate::parse_from_str(
-                                    &format!("01 {}", trimmed_value),
-                                    "%d %B %Y",
-                                ) {
-                                    Some(parsed)
-                                } else if let Ok(parsed) = chrono::NaiveDate::parse_from_str(
-                                    &format!("01 {}", trimmed_value),
-                                    "%d %b %Y",
-                                ) {
-                                    Some(parsed)
-                                } else {
-                                    None
-                                }
-                            }
-                            _ => None,
-                        }
-                    })
-                    .ok_or_else(|| {
-                        ProcessorError::Processing(format!("Failed to parse date {:#?}", value))
-                    });
+                let matches: Vec<(&str, chrono::NaiveDate)> = profile
+                    .date_formats()
+                    .into_iter()
+                    .filter_map(|fmt| parse_date(trimmed_value, fmt).map(|date| (fmt, date)))
+                    .collect();
+                self.check_date_ambiguity(column, row_num, trimmed_value, &matches);
 
-                if let Ok(date) = date_result {
+                if let Some((_, date)) = matches.first() {
                     Ok(JsonValue::String(date.format("%Y-%m-%d").to_string()))
                 } else {
                     let msg = format!("Failed to parse date {:#?}", value);
@@ -74,12 +72,12 @@ impl InstanceProcessor {
                             format!("{}, using raw value", msg),
                             Some("date_validation".to_string()),
                         );
-                        Ok(JsonValue::String(value.to_string()))
+                        Ok(raw_string(value))
                     }
                 }
             }
             PropertyDatatype::Integer => {
-                let cleaned_value = value.replace(['$', '%', ','], "");
+                let cleaned_value = self.parse_profile_for(column).normalize_number(value);
                 if let Ok(num) = cleaned_value.parse::<i64>() {
                     Ok(JsonValue::Number(serde_json::Number::from(num)))
                 } else if self.is_strict {
@@ -99,32 +97,29 @@ impl InstanceProcessor {
                         ),
                         Some("value_validation".to_string()),
                     );
-                    Ok(JsonValue::String(value.to_string()))
+                    Ok(raw_string(value))
                 }
             }
             PropertyDatatype::Decimal => {
-                let cleaned_value = value.replace(['$', '%', ','], "");
-                if let Ok(num) = cleaned_value.parse::<f64>() {
-                    Ok(JsonValue::Number(
-                        serde_json::Number::from_f64(num).unwrap(),
-                    ))
-                } else {
-                    Ok(JsonValue::String(cleaned_value.to_string()))
+                // Kept as a string so the exact digits survive; the context types it as xsd:decimal.
+                // Percentages are read as fractions unless the property declares a unit
+                let (number, is_percent) = match value.trim().strip_suffix('%') {
+                    Some(number) => (number, true),
+                    None => (value, false),
+                };
+                let cleaned_value = self.parse_profile_for(column).normalize_number(number);
+                match canonical_decimal(&cleaned_value) {
+                    Some(decimal) if is_percent => {
+                        Ok(JsonValue::String(percent_to_fraction(&decimal)))
+                    }
+                    Some(decimal) => Ok(JsonValue::String(decimal)),
+                    None => self.invalid_value(value, "decimal", header_name, row_num),
                 }
             }
             PropertyDatatype::String => Ok(JsonValue::String(value.to_string())),
             PropertyDatatype::Boolean => {
-                let cleaned_value = value.to_lowercase();
-                if cleaned_value == "true"
-                    || cleaned_value == "false"
-                    || cleaned_value == "1"
-                    || cleaned_value == "0"
-                    || cleaned_value == "yes"
-                    || cleaned_value == "no"
-                {
-                    Ok(JsonValue::Bool(
-                        cleaned_value == "true" || cleaned_value == "1" || cleaned_value == "yes",
-                    ))
+                if let Some(boolean) = self.parse_profile_for(column).parse_bool(value) {
+                    Ok(JsonValue::Bool(boolean))
                 } else {
                     let msg = format!(
                         "[Column: {}, Row: {}], Invalid boolean value: {}",
@@ -139,22 +134,140 @@ impl InstanceProcessor {
                             format!("{}, using raw value", msg),
                             Some("boolean_validation".to_string()),
                         );
-                        Ok(JsonValue::String(value.to_string()))
+                        Ok(raw_string(value))
                     }
                 }
             }
-            PropertyDatatype::URI(target_class) | PropertyDatatype::Picklist(target_class) => {
-                self.process_class_restricted_value(header_name, value, target_class, datatype)
+            PropertyDatatype::DateTime => {
+                let profile = self.parse_profile_for(column);
+                match parse_date_time(value.trim(), profile) {
+                    Some(date_time) => Ok(JsonValue::String(date_time)),
+                    None => self.invalid_value(value, "dateTime", header_name, row_num),
+                }
+            }
+            PropertyDatatype::Time => {
+                let trimmed_value = value.trim();
+                match TIME_FORMATS
+                    .iter()
+                    .find_map(|fmt| chrono::NaiveTime::parse_from_str(trimmed_value, fmt).ok())
+                {
+                    Some(time) => Ok(JsonValue::String(time.format("%H:%M:%S%.f").to_string())),
+                    None => self.invalid_value(value, "time", header_name, row_num),
+                }
             }
+            PropertyDatatype::Duration => match parse_duration(value.trim()) {
+                Some(duration) => Ok(JsonValue::String(duration)),
+                None => self.invalid_value(value, "duration", header_name, row_num),
+            },
+            PropertyDatatype::GYear => {
+                let trimmed_value = value.trim();
+                let digits = trimmed_value.strip_prefix('-').unwrap_or(trimmed_value);
+                if digits.len() >= 4 && digits.chars().all(|c| c.is_ascii_digit()) {
+                    Ok(JsonValue::String(trimmed_value.to_string()))
+                } else {
+                    self.invalid_value(value, "gYear", header_name, row_num)
+                }
+            }
+            // Parsed like Integer, which is already 64-bit; only the context datatype differs
+            PropertyDatatype::Long => {
+                let cleaned_value = self.parse_profile_for(column).normalize_number(value);
+                match cleaned_value.parse::<i64>() {
+                    Ok(num) => Ok(JsonValue::Number(serde_json::Number::from(num))),
+                    Err(_) => self.invalid_value(value, "long", header_name, row_num),
+                }
+            }
+            PropertyDatatype::AnyURI => {
+                let trimmed_value = value.trim();
+                if is_valid_url(trimmed_value) {
+                    Ok(JsonValue::String(trimmed_value.to_string()))
+                } else {
+                    self.invalid_value(value, "anyURI", header_name, row_num)
+                }
+            }
+            PropertyDatatype::LangString => Ok(JsonValue::String(value.to_string())),
+            PropertyDatatype::URI(target_class) | PropertyDatatype::Picklist(target_class) => self
+                .process_class_restricted_value(
+                    header_name,
+                    value,
+                    target_class,
+                    datatype,
+                    row_num,
+                ),
         }
     }
 
+    /// Report a value that is not valid for its datatype, keeping the raw value as a string outside
+    /// strict mode
+    fn invalid_value(
+        &mut self,
+        value: &str,
+        datatype: &str,
+        header_name: &str,
+        row_num: usize,
+    ) -> Result<JsonValue, ProcessorError> {
+        let msg = format!(
+            "[Column: {}, Row: {}], Invalid {} value: {}",
+            header_name,
+            row_num + 1,
+            datatype,
+            value
+        );
+        if self.is_strict {
+            Err(ProcessorError::Processing(msg))
+        } else {
+            self.processing_state.add_warning(
+                format!("{}, using raw value", msg),
+                Some("value_validation".to_string()),
+            );
+            Ok(raw_string(value))
+        }
+    }
+
+    /// The parse profile for a column, falling back to the step's profile
+    fn parse_profile_for(&self, column: &str) -> &ParseProfile {
+        self.column_parse_profiles
+            .get(column)
+            .unwrap_or(&self.parse_profile)
+    }
+
+    /// Flag, once per column, a date that reads as different dates under different formats
+    fn check_date_ambiguity(
+        &mut self,
+        column: &str,
+        row_num: usize,
+        value: &str,
+        matches: &[(&str, chrono::NaiveDate)],
+    ) {
+        let Some((_, first_date)) = matches.first() else {
+            return;
+        };
+        if matches.iter().all(|(_, date)| date == first_date)
+            || !self.ambiguous_date_columns.insert(column.to_string())
+        {
+            return;
+        }
+        let formats: Vec<&str> = matches.iter().map(|(fmt, _)| *fmt).collect();
+        self.processing_state.add_warning(
+            format!(
+                "[Source: {}, Column: {}, Row: {}], Date {:?} can be read with more than one format ({}), using {}. Set dateFormats in a parse profile for this column",
+                self.current_source,
+                column,
+                row_num + 1,
+                value,
+                formats.join(", "),
+                formats[0]
+            ),
+            Some("date_validation".to_string()),
+        );
+    }
+
     pub(crate) fn process_class_restricted_value(
         &mut self,
         header: &str,
         value: &str,
         target_class: &Option<String>,
         datatype: &PropertyDatatype,
+        row_num: usize,
     ) -> Result<JsonValue, ProcessorError> {
         let class_match = match target_class {
             Some(target_class) => {
@@ -179,6 +292,9 @@ impl InstanceProcessor {
             None => None,
         };
 
+        // Values found in the class's picklist are valid even when no instance for them is loaded,
+        // such as the oneOf values of an imported ontology
+        let mut is_picklist_value = false;
         if matches!(datatype, PropertyDatatype::Picklist(_)) {
             let (class_match, class_match_class_definition) = class_match.ok_or_else(|| {
                 ProcessorError::Processing(format!(
@@ -216,6 +332,7 @@ impl InstanceProcessor {
             let does_picklist_contain_value = enum_picklist
                 .iter()
                 .any(|picklist_value| picklist_value.to_string() == iri);
+            is_picklist_value = does_picklist_contain_value;
             if !does_picklist_contain_value {
                 let error_string = format!(
                     "Value \"{}\" ({}) for property \"{}\" not found in {} picklist: {:?}",
@@ -233,14 +350,309 @@ impl InstanceProcessor {
         let class_match_id = class_match.map(|(id, _)| id.clone());
 
         if let Some(class_id) = class_match_id {
-            if self.is_namespace_iris {
-                let iri = format!("{}/{}", to_kebab_case(class_id.to_string().as_ref()), value);
-                Ok(JsonValue::String(iri))
+            let iri = if self.is_namespace_iris {
+                format!("{}/{}", to_kebab_case(class_id.to_string().as_ref()), value)
             } else {
-                Ok(JsonValue::String(value.to_string()))
+                value.to_string()
+            };
+            if !is_picklist_value {
+                self.record_reference(header, row_num, &iri);
             }
+            Ok(JsonValue::String(iri))
         } else {
             Ok(JsonValue::String(value.to_string()))
         }
     }
 }
+
+/// Parse a date with a single format, reading year-only and year-month formats as the first day
+/// of the period
+/// A value that failed to parse as its datatype, typed as a plain string so the term's datatype
+/// in the context does not turn it into an invalid typed literal
+fn raw_string(value: &str) -> JsonValue {
+    json!({ "@value": value, "@type": "xsd:string" })
+}
+
+fn parse_date(value: &str, fmt: &str) -> Option<chrono::NaiveDate> {
+    // First try exact parsing
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, fmt) {
+        return Some(date);
+    }
+
+    // Handle partial dates
+    match fmt {
+        // Year only - default to Jan 1
+        "%Y" => value.parse::<i32>().ok().and_then(|year| {
+            chrono::NaiveDate::from_ymd_opt(
+                year, 1, // January
+                1, // 1st
+            )
+        }),
+        // Year-month formats - default to 1st of month
+        "%Y-%m" | "%Y/%m" | "%b %Y" | "%B %Y" | "%m-%Y" => {
+            if let Ok(parsed) = chrono::NaiveDate::parse_from_str(
+                &format!("{}-01", value.replace("/", "-")),
+                "%Y-%m-%d",
+            ) {
+                Some(parsed)
+            } else if let Ok(parsed) =
+                chrono::NaiveDate::parse_from_str(&format!("01 {}", value), "%d %B %Y")
+            {
+                Some(parsed)
+            } else if let Ok(parsed) =
+                chrono::NaiveDate::parse_from_str(&format!("01 {}", value), "%d %b %Y")
+            {
+                Some(parsed)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Parse a timestamp as RFC 3339 or one of the profile's formats. Timestamps without a zone take
+/// the profile's timeZone if set, and are otherwise kept without one
+fn parse_date_time(value: &str, profile: &ParseProfile) -> Option<String> {
+    let formats = profile.date_time_formats();
+    let format_zoned = |date_time: DateTime<FixedOffset>| {
+        if profile.normalize_to_utc() {
+            date_time
+                .with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+        } else {
+            date_time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+        }
+    };
+
+    let zoned = DateTime::parse_from_rfc3339(value).ok().or_else(|| {
+        formats
+            .iter()
+            .find_map(|fmt| DateTime::parse_from_str(value, fmt).ok())
+    });
+    if let Some(date_time) = zoned {
+        return Some(format_zoned(date_time));
+    }
+
+    let naive = formats
+        .iter()
+        .find_map(|fmt| chrono::NaiveDateTime::parse_from_str(value, fmt).ok())?;
+    match profile.time_zone() {
+        Some(offset) => offset
+            .from_local_datetime(&naive)
+            .single()
+            .map(format_zoned),
+        None => Some(naive.format("%Y-%m-%dT%H:%M:%S%.f").to_string()),
+    }
+}
+
+/// Read an ISO 8601 duration (`P1DT2H`), or a clock duration (`02:30:00`) converted to one
+fn parse_duration(value: &str) -> Option<String> {
+    static ISO_DURATION: OnceLock<Regex> = OnceLock::new();
+    let iso_duration = ISO_DURATION.get_or_init(|| {
+        Regex::new(r"^-?P(\d+Y)?(\d+M)?(\d+W)?(\d+D)?(T(\d+H)?(\d+M)?(\d+(\.\d+)?S)?)?$").unwrap()
+    });
+    if iso_duration.is_match(value) {
+        let unsigned = value.trim_start_matches('-');
+        return (unsigned != "P" && !unsigned.ends_with('T')).then(|| value.to_string());
+    }
+
+    let parts: Vec<&str> = value.split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    let hours: u64 = parts[0].parse().ok()?;
+    let minutes: u64 = parts[1].parse().ok()?;
+    let seconds: f64 = match parts.get(2) {
+        Some(seconds) => seconds.parse().ok()?,
+        None => 0.0,
+    };
+    if minutes >= 60 || !(0.0..60.0).contains(&seconds) {
+        return None;
+    }
+    let mut duration = "PT".to_string();
+    if hours > 0 {
+        duration.push_str(&format!("{}H", hours));
+    }
+    if minutes > 0 {
+        duration.push_str(&format!("{}M", minutes));
+    }
+    if seconds > 0.0 || duration == "PT" {
+        duration.push_str(&format!("{}S", seconds));
+    }
+    Some(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_duration;
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::{json, Value as JsonValue};
+
+    fn order_model() -> String {
+        model_csv(
+            &[],
+            "\
+Order,Order ID,Order,An order,Order ID,Identifier,@id,
+Order,Order Date,Order,An order,Order Date,When it was placed,Date,
+Order,Total,Order,An order,Total,Order total,Decimal,
+",
+        )
+    }
+
+    /// A manifest importing `orders.csv` as Orders, with extra step `fields`
+    fn order_manifest(mut fields: JsonValue) -> String {
+        fields["instanceType"] = json!("Order");
+        TestManifest::new()
+            .instance_step("orders.csv", "BasicInstanceStep", fields)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_column_parse_profile_for_overridden_column() {
+        let manifest = order_manifest(json!({
+            "overrides": [
+                { "column": "Datum", "mapTo": "Order Date" },
+                { "column": "Summe", "mapTo": "Total" }
+            ],
+            "columnParseProfiles": {
+                "Datum": { "dateFormats": ["%d/%m/%Y"] },
+                "Summe": { "decimalSeparator": ",", "thousandsSeparator": "." }
+            }
+        }));
+        let orders = "Order ID,Datum,Summe\nO1,03/04/2024,\"1.234,50\"\n";
+        let run = run_import(
+            &manifest,
+            &[("model.csv", &order_model()), ("orders.csv", orders)],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert!(run.warnings.is_empty(), "{:?}", run.warnings);
+        let order = run.instance("O1").unwrap();
+        assert_eq!(order["Order Date"], json!(["2024-04-03"]));
+        assert_eq!(order["Total"], json!(["1234.50"]));
+    }
+
+    #[tokio::test]
+    async fn test_extended_datatypes() {
+        let model = model_csv(
+            &[],
+            "\
+Event,Event ID,Event,An event,Event ID,Identifier,@id,
+Event,Starts,Event,An event,Starts,Start timestamp,DateTime,
+Event,Doors,Event,An event,Doors,Door opening time,Time,
+Event,Length,Event,An event,Length,Running time,Duration,
+Event,Season,Event,An event,Season,Season year,gYear,
+Event,Views,Event,An event,Views,Page views,Long,
+Event,Homepage,Event,An event,Homepage,Web page,anyURI,
+Event,Title,Event,An event,Title,Localized title,langString,
+",
+        );
+        let manifest = TestManifest::new()
+            .with(
+                "parseProfile",
+                json!({ "timeZone": "+02:00", "normalizeToUtc": true }),
+            )
+            .instance_step(
+                "events.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Event", "columnLanguages": { "Title": "fr" } }),
+            )
+            .build();
+        let events = "\
+Event ID,Starts,Doors,Length,Season,Views,Homepage,Title
+E1,2024-06-01 20:00:00,19:30,1:45,2024,9007199254740993,https://example.com/e1,Le Concert
+E2,2024-06-02T20:00:00-05:00,7pm,90 minutes,24,many,not a url,
+";
+        let files = [("model.csv", model.as_str()), ("events.csv", events)];
+
+        let run = run_import(&manifest, &files, false).await;
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let concert = run.instance("E1").unwrap();
+        assert_eq!(concert["Starts"], json!(["2024-06-01T18:00:00Z"]));
+        assert_eq!(concert["Doors"], json!(["19:30:00"]));
+        assert_eq!(concert["Length"], json!(["PT1H45M"]));
+        assert_eq!(concert["Season"], json!(["2024"]));
+        assert_eq!(concert["Views"], json!([9007199254740993i64]));
+        assert_eq!(concert["Homepage"], json!(["https://example.com/e1"]));
+        assert_eq!(
+            concert["Title"],
+            json!([{ "@value": "Le Concert", "@language": "fr" }])
+        );
+        assert_eq!(
+            run.instance("E2").unwrap()["Starts"],
+            json!(["2024-06-03T01:00:00Z"])
+        );
+
+        for (property, range) in [
+            ("starts", "xsd:dateTime"),
+            ("doors", "xsd:time"),
+            ("length", "xsd:duration"),
+            ("season", "xsd:gYear"),
+            ("views", "xsd:long"),
+            ("homepage", "xsd:anyURI"),
+            ("title", "rdf:langString"),
+        ] {
+            let term = run
+                .term(&format!("http://example.com/terms/{}", property))
+                .unwrap();
+            assert_eq!(term["rdfs:range"], json!([range]), "{property}");
+        }
+
+        let run = run_import(&manifest, &files, true).await;
+        for datatype in ["time", "duration", "gYear", "long", "anyURI"] {
+            assert!(
+                run.has_error(&format!("Row: 2], Invalid {} value", datatype)),
+                "{datatype}: {:?}",
+                run.errors
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("P1Y2M3DT4H").as_deref(), Some("P1Y2M3DT4H"));
+        assert_eq!(parse_duration("1:45").as_deref(), Some("PT1H45M"));
+        assert_eq!(parse_duration("0:00:30.5").as_deref(), Some("PT30.5S"));
+        assert_eq!(parse_duration("P"), None);
+        assert_eq!(parse_duration("PT"), None);
+        assert_eq!(parse_duration("1:75"), None);
+    }
+
+    #[tokio::test]
+    async fn test_decimals_are_exact() {
+        let manifest = order_manifest(json!({}));
+        let orders = "\
+Order ID,Total
+O1,\"$1,305.00\"
+O2,0.1000000000000000055511151231257827
+O3,12.5%
+O4,NaN
+O5,twelve
+";
+        let model = order_model();
+        let files = [("model.csv", model.as_str()), ("orders.csv", orders)];
+
+        let run = run_import(&manifest, &files, false).await;
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let total = |id: &str| run.instance(id).unwrap()["Total"].clone();
+        assert_eq!(total("O1"), json!(["1305.00"]));
+        assert_eq!(total("O2"), json!(["0.1000000000000000055511151231257827"]));
+        assert_eq!(total("O3"), json!(["0.125"]));
+        // Invalid decimals are reported and kept as written, typed as strings so the context's
+        // xsd:decimal coercion does not apply to them
+        assert_eq!(run.instances["@context"]["Total"]["@type"], "xsd:decimal");
+        assert_eq!(
+            total("O4"),
+            json!([{ "@value": "NaN", "@type": "xsd:string" }])
+        );
+        assert!(run.has_warning("[Column: Total, Row: 4], Invalid decimal value: NaN"));
+        assert!(run.has_warning("[Column: Total, Row: 5], Invalid decimal value: twelve"));
+
+        let run = run_import(&manifest, &files, true).await;
+        assert!(run.has_error("[Column: Total, Row: 4], Invalid decimal value: NaN"));
+        assert!(run.has_error("[Column: Total, Row: 5], Invalid decimal value: twelve"));
+    }
+}
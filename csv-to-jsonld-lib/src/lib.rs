@@ -16,8 +16,8 @@ pub use error::{ProcessingMessage, ProcessingOutcome, ProcessingState, Processor
 pub use excel::ExcelReader;
 pub use instance::{InstanceManager, JsonLdInstance, JsonLdInstances};
 pub use manifest::{
-    ImportSection, ImportStep, InstanceStep, Manifest, ModelStep, ReadSeek, StepType,
-    StorageLocation,
+    ImportSection, ImportStep, InstanceStep, Manifest, ModelOutputFormat, ModelStep, ReadSeek,
+    StepType, StorageLocation,
 };
 pub use processor::{Processor, ProcessorBuilder};
 pub use types::{ColumnOverride, JsonLdContext, JsonLdVocabulary, VocabularyMap, VocabularyTerm};
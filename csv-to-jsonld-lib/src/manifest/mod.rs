@@ -1,11 +1,15 @@
 use crate::contains_variant;
 use crate::error::{ProcessingState, ProcessorError};
-use crate::types::{ColumnOverride, ExtraItem, PivotColumn};
+use crate::types::{
+    ColumnOverride, ExtraItem, ExtractedEntity, Lookup, NestedObject, OnEntity, ParseProfile,
+    PivotColumn, RepeatingGroup,
+};
+use crate::utils::{is_language_tag, is_valid_url, template_placeholders};
 use csv::StringRecord;
 use json_comments::StripComments;
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Display;
 use std::io::{self, Cursor, Read, Seek};
 use std::path::{Path, PathBuf};
@@ -371,6 +375,8 @@ pub enum InstanceStep {
     PicklistStep,
     SubClassInstanceStep,
     PropertiesInstanceStep,
+    UnpivotInstanceStep,
+    RelationshipInstanceStep,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -404,6 +410,12 @@ impl Display for StepType {
             StepType::InstanceStep(InstanceStep::PropertiesInstanceStep) => {
                 write!(f, "PropertiesInstanceStep")
             }
+            StepType::InstanceStep(InstanceStep::UnpivotInstanceStep) => {
+                write!(f, "UnpivotInstanceStep")
+            }
+            StepType::InstanceStep(InstanceStep::RelationshipInstanceStep) => {
+                write!(f, "RelationshipInstanceStep")
+            }
         }
     }
 }
@@ -447,6 +459,12 @@ impl<'de> Deserialize<'de> for StepType {
                     "PropertiesInstanceStep" => {
                         Ok(StepType::InstanceStep(InstanceStep::PropertiesInstanceStep))
                     }
+                    "UnpivotInstanceStep" => {
+                        Ok(StepType::InstanceStep(InstanceStep::UnpivotInstanceStep))
+                    }
+                    "RelationshipInstanceStep" => Ok(StepType::InstanceStep(
+                        InstanceStep::RelationshipInstanceStep,
+                    )),
                     _ => Err(de::Error::unknown_variant(
                         value,
                         &[
@@ -457,6 +475,8 @@ impl<'de> Deserialize<'de> for StepType {
                             "PicklistStep",
                             "SubClassInstanceStep",
                             "PropertiesInstanceStep",
+                            "UnpivotInstanceStep",
+                            "RelationshipInstanceStep",
                         ],
                     )),
                 }
@@ -497,6 +517,34 @@ pub struct ImportStep {
     pub delimit_values_on: Option<String>,
     #[serde(rename = "mapToLabel")]
     pub map_to_label: Option<String>,
+    #[serde(rename = "idTemplate")]
+    pub id_template: Option<String>,
+    #[serde(default)]
+    pub nested: Vec<NestedObject>,
+    #[serde(default, rename = "repeatingGroups")]
+    pub repeating_groups: Vec<RepeatingGroup>,
+    #[serde(rename = "unpivotColumns")]
+    pub unpivot_columns: Option<Vec<String>>,
+    #[serde(default)]
+    pub lookups: Vec<Lookup>,
+    #[serde(rename = "parseProfile")]
+    pub parse_profile: Option<ParseProfile>,
+    #[serde(default, rename = "columnParseProfiles")]
+    pub column_parse_profiles: HashMap<String, ParseProfile>,
+    /// Language tags for string columns, for columns whose header does not carry one as a
+    /// "Name@de" suffix
+    #[serde(default, rename = "columnLanguages")]
+    pub column_languages: HashMap<String, String>,
+    #[serde(default, rename = "extractEntities")]
+    pub extract_entities: Vec<ExtractedEntity>,
+    #[serde(rename = "sourceColumn")]
+    pub source_column: Option<String>,
+    #[serde(rename = "targetColumn")]
+    pub target_column: Option<String>,
+    #[serde(rename = "relationshipProperty")]
+    pub relationship_property: Option<String>,
+    #[serde(rename = "inverseProperty")]
+    pub inverse_property: Option<String>,
 }
 
 impl ImportStep {
@@ -528,9 +576,27 @@ pub struct ImportSection {
     pub base_iri: String,
     #[serde(default, rename = "namespaceIris")]
     pub namespace_iris: bool,
+    /// Vocabulary to write the model with; only read from the model section
+    #[serde(default, rename = "outputFormat")]
+    pub output_format: ModelOutputFormat,
+    /// Existing ontology (Turtle or JSON-LD) the model steps extend; only read from the model
+    /// section
+    #[serde(default, rename = "baseOntology")]
+    pub base_ontology: Option<StorageLocation>,
     pub sequence: Vec<ImportStep>,
 }
 
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum ModelOutputFormat {
+    /// RDFS classes and properties in a Fluree data model
+    #[default]
+    #[serde(rename = "RDFS")]
+    Rdfs,
+    /// An OWL ontology
+    #[serde(rename = "OWL")]
+    Owl,
+}
+
 impl ImportSection {
     pub fn deduplicate_steps(&mut self) -> Result<(), Vec<ImportStep>> {
         let mut seen_step_paths = HashSet::new();
@@ -583,6 +649,12 @@ pub struct Manifest {
     pub model: ImportSection,
     #[serde(default)]
     pub instances: ImportSection,
+    #[serde(default, rename = "parseProfile")]
+    pub parse_profile: ParseProfile,
+    /// Namespaces for prefixed names (`schema:name`) in model CSV cells, also declared in the
+    /// output contexts
+    #[serde(default)]
+    pub prefixes: BTreeMap<String, String>,
 }
 
 fn handle_step_deduplication(
@@ -642,6 +714,54 @@ impl Manifest {
             ));
         }
 
+        let parse_profiles = std::iter::once(&self.parse_profile).chain(
+            self.instances.sequence.iter().flat_map(|step| {
+                step.parse_profile
+                    .iter()
+                    .chain(step.column_parse_profiles.values())
+            }),
+        );
+        for profile in parse_profiles {
+            if let Err(e) = profile.validate() {
+                state.add_error_from(e);
+            }
+        }
+
+        for step in &self.instances.sequence {
+            for (column, language) in &step.column_languages {
+                if !is_language_tag(language) {
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "Invalid language tag '{}' for column '{}' in step {}",
+                        language,
+                        column,
+                        step.id()
+                    )));
+                }
+            }
+        }
+
+        if self.instances.output_format != ModelOutputFormat::Rdfs {
+            state.add_warning_from(ProcessorError::InvalidManifest(
+                "outputFormat only applies to the model section and is ignored for instances"
+                    .into(),
+            ));
+        }
+        if self.instances.base_ontology.is_some() {
+            state.add_warning_from(ProcessorError::InvalidManifest(
+                "baseOntology only applies to the model section and is ignored for instances"
+                    .into(),
+            ));
+        }
+
+        for (prefix, namespace) in &self.prefixes {
+            if prefix.is_empty() || prefix.contains(':') || !is_valid_url(namespace) {
+                state.add_error_from(ProcessorError::InvalidManifest(format!(
+                    "Invalid prefix '{}': prefixes must be names without ':' mapped to namespace IRIs, found '{}'",
+                    prefix, namespace
+                )));
+            }
+        }
+
         if self.type_ == "ExcelImportManifest" && self.excel_file.is_none() {
             state.add_error_from(ProcessorError::InvalidManifest(
                 "ExcelImportManifest requires excel_file to be specified".into(),
@@ -689,6 +809,21 @@ impl Manifest {
                 }
             }
 
+            for extra_item in &step.extra_items {
+                if matches!(extra_item.on_entity, OnEntity::Instance) {
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "extraItem '{}' on a model step must have onEntity of CLASS or PROPERTY",
+                        extra_item.map_to
+                    )));
+                }
+                if extra_item.column.is_empty() {
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "extraItem '{}' on a model step requires a column",
+                        extra_item.map_to
+                    )));
+                }
+            }
+
             // Validate Excel sheet reference if needed
             if self.type_ == "ExcelImportManifest" && step.sheet.is_none() {
                 state.add_error_from(ProcessorError::InvalidManifest(format!(
@@ -724,14 +859,14 @@ impl Manifest {
             if instance_steps.is_empty() {
                 tracing::error!("No valid instance step type found: {:?}", step.types);
                 state.add_error_from(ProcessorError::InvalidManifest(
-                    "Instance sequence steps must include InstanceStep type: BasicInstanceStep, SubClassInstanceStep, or PropertiesInstanceStep".into(),
+                    "Instance sequence steps must include InstanceStep type: BasicInstanceStep, SubClassInstanceStep, PropertiesInstanceStep, UnpivotInstanceStep, or RelationshipInstanceStep".into(),
                 ));
             }
 
             if instance_steps.len() > 1 {
                 tracing::error!("Multiple instance step types found: {:?}", step.types);
                 state.add_error_from(ProcessorError::InvalidManifest(
-                    "Instance sequence steps must include only one InstanceStep type: BasicInstanceStep, SubClassInstanceStep, or PropertiesInstanceStep".into(),
+                    "Instance sequence steps must include only one InstanceStep type: BasicInstanceStep, SubClassInstanceStep, PropertiesInstanceStep, UnpivotInstanceStep, or RelationshipInstanceStep".into(),
                 ));
             }
 
@@ -744,6 +879,175 @@ impl Manifest {
                 }
             }
 
+            if step.unpivot_columns.is_some()
+                && !matches!(
+                    instance_steps[0],
+                    StepType::InstanceStep(InstanceStep::UnpivotInstanceStep)
+                )
+            {
+                state.add_error_from(ProcessorError::InvalidManifest(
+                    "unpivotColumns is only supported on UnpivotInstanceStep".into(),
+                ));
+            }
+
+            let relationship_fields = [
+                ("sourceColumn", step.source_column.is_some()),
+                ("targetColumn", step.target_column.is_some()),
+                ("relationshipProperty", step.relationship_property.is_some()),
+            ];
+            if let StepType::InstanceStep(InstanceStep::RelationshipInstanceStep) =
+                instance_steps[0]
+            {
+                for (field, _) in relationship_fields.iter().filter(|(_, is_set)| !is_set) {
+                    tracing::error!("RelationshipInstanceStep requires {} field", field);
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "RelationshipInstanceStep requires {} field",
+                        field
+                    )));
+                }
+            } else if relationship_fields.iter().any(|(_, is_set)| *is_set)
+                || step.inverse_property.is_some()
+            {
+                state.add_error_from(ProcessorError::InvalidManifest(
+                    "sourceColumn, targetColumn, relationshipProperty and inverseProperty are only supported on RelationshipInstanceStep".into(),
+                ));
+            }
+
+            if let Some(id_template) = &step.id_template {
+                if let StepType::InstanceStep(InstanceStep::PropertiesInstanceStep) =
+                    instance_steps[0]
+                {
+                    state.add_error_from(ProcessorError::InvalidManifest(
+                        "idTemplate is not supported on PropertiesInstanceStep".into(),
+                    ));
+                }
+                if let Err(e) = template_placeholders(id_template) {
+                    tracing::error!("Invalid idTemplate: {}", id_template);
+                    state.add_error_from(e);
+                }
+            }
+
+            for extra_item in &step.extra_items {
+                if !matches!(extra_item.on_entity, OnEntity::Instance) {
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "extraItem '{}' on an instance step must have onEntity of INSTANCE",
+                        extra_item.map_to
+                    )));
+                }
+                if extra_item.column.is_empty() == extra_item.value.is_none() {
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "extraItem '{}' on an instance step requires exactly one of column or value",
+                        extra_item.map_to
+                    )));
+                }
+            }
+
+            for pivot_column in step.pivot_columns.iter().flatten() {
+                if let Some(id_template) = &pivot_column.id_template {
+                    if let Err(e) = template_placeholders(id_template) {
+                        tracing::error!("Invalid pivot column idTemplate: {}", id_template);
+                        state.add_error_from(e);
+                    }
+                }
+            }
+
+            for group in &step.repeating_groups {
+                if group.columns.is_empty() {
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "Repeating group for '{}' requires at least one column",
+                        group.new_relationship_property
+                    )));
+                }
+                for column in &group.columns {
+                    if !column.column.contains(RepeatingGroup::INDEX_PLACEHOLDER) {
+                        state.add_error_from(ProcessorError::InvalidManifest(format!(
+                            "Repeating group column '{}' must contain the index placeholder {}",
+                            column.column,
+                            RepeatingGroup::INDEX_PLACEHOLDER
+                        )));
+                    }
+                }
+                if let Some(id_template) = group.id_template_for("0") {
+                    if let Err(e) = template_placeholders(&id_template) {
+                        tracing::error!("Invalid repeating group idTemplate: {}", id_template);
+                        state.add_error_from(e);
+                    }
+                }
+            }
+
+            let mut nested_paths = HashSet::new();
+            for nested in &step.nested {
+                if nested.path.is_empty()
+                    || nested.path.starts_with('.')
+                    || nested.path.ends_with('.')
+                {
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "Invalid nested path '{}'",
+                        nested.path
+                    )));
+                }
+                if !nested_paths.insert(nested.path.as_str()) {
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "Nested path '{}' is declared more than once",
+                        nested.path
+                    )));
+                }
+                if nested.instance_type.is_empty() || nested.property.is_empty() {
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "Nested path '{}' requires instanceType and property",
+                        nested.path
+                    )));
+                }
+                if let Some(id_template) = &nested.id_template {
+                    if nested.blank_node {
+                        state.add_error_from(ProcessorError::InvalidManifest(format!(
+                            "Nested path '{}' cannot have both idTemplate and blankNode",
+                            nested.path
+                        )));
+                    }
+                    if let Err(e) = template_placeholders(id_template) {
+                        tracing::error!("Invalid nested idTemplate: {}", id_template);
+                        state.add_error_from(e);
+                    }
+                }
+            }
+
+            if !step.extract_entities.is_empty()
+                && !matches!(
+                    instance_steps[0],
+                    StepType::InstanceStep(InstanceStep::BasicInstanceStep)
+                        | StepType::InstanceStep(InstanceStep::SubClassInstanceStep)
+                )
+            {
+                state.add_error_from(ProcessorError::InvalidManifest(
+                    "extractEntities is only supported on BasicInstanceStep and SubClassInstanceStep".into(),
+                ));
+            }
+            if !step.lookups.is_empty()
+                && !matches!(
+                    instance_steps[0],
+                    StepType::InstanceStep(InstanceStep::BasicInstanceStep)
+                        | StepType::InstanceStep(InstanceStep::SubClassInstanceStep)
+                )
+            {
+                state.add_error_from(ProcessorError::InvalidManifest(
+                    "lookups is only supported on BasicInstanceStep and SubClassInstanceStep"
+                        .into(),
+                ));
+            }
+
+            for entity in &step.extract_entities {
+                if entity.reference_column.is_empty()
+                    || entity.instance_type.is_empty()
+                    || entity.columns.is_empty()
+                {
+                    state.add_error_from(ProcessorError::InvalidManifest(format!(
+                        "extractEntities entry for '{}' requires referenceColumn, instanceType and at least one column",
+                        entity.reference_column
+                    )));
+                }
+            }
+
             // Validate Excel sheet reference if needed
             if self.type_ == "ExcelImportManifest" && step.sheet.is_none() {
                 state.add_error_from(ProcessorError::InvalidManifest(format!(
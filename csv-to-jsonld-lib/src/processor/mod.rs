@@ -9,6 +9,9 @@ use std::mem::take;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+#[cfg(test)]
+pub(crate) mod testing;
+
 pub struct ProcessorBuilder {
     manifest: Manifest,
     base_path: Option<PathBuf>,
@@ -17,6 +20,7 @@ pub struct ProcessorBuilder {
     is_strict: bool,
     export_vocab_meta: Option<StorageLocation>,
     vocab_meta_path: Option<StorageLocation>,
+    shapes_output_path: Option<StorageLocation>,
     s3_client: Option<aws_sdk_s3::Client>,
 }
 
@@ -30,6 +34,7 @@ impl ProcessorBuilder {
             is_strict: false,
             export_vocab_meta: None,
             vocab_meta_path: None,
+            shapes_output_path: None,
             s3_client: None,
         }
     }
@@ -73,6 +78,18 @@ impl ProcessorBuilder {
         Ok(self)
     }
 
+    /// Also write SHACL shapes for the vocabulary, to "shapes.jsonld" when the path is a directory
+    pub fn with_shapes_output_path(mut self, path: Option<StorageLocation>) -> Self {
+        self.shapes_output_path = path.map(|path| {
+            if path.is_dir() {
+                path.join("shapes.jsonld")
+            } else {
+                path
+            }
+        });
+        self
+    }
+
     pub fn with_s3_client(mut self, s3_client: aws_sdk_s3::Client) -> Self {
         self.s3_client = Some(s3_client);
         self
@@ -109,7 +126,7 @@ impl ProcessorBuilder {
             }
         };
 
-        Processor::with_base_path(
+        let mut processor = Processor::with_base_path(
             self.manifest,
             base_path,
             self.is_strict,
@@ -119,7 +136,9 @@ impl ProcessorBuilder {
             self.vocab_meta_path,
             self.s3_client.as_ref(),
         )
-        .await
+        .await?;
+        processor.shapes_output_path = self.shapes_output_path;
+        Ok(processor)
     }
 }
 
@@ -131,6 +150,7 @@ pub struct Processor {
     instance_output_path: StorageLocation,
     model_output_path: StorageLocation,
     export_vocab_meta: Option<StorageLocation>,
+    shapes_output_path: Option<StorageLocation>,
     processing_state: ProcessingState,
     s3_client: Option<aws_sdk_s3::Client>,
 }
@@ -166,6 +186,7 @@ impl Processor {
             model_output_path,
             manifest,
             export_vocab_meta,
+            shapes_output_path: None,
             processing_state: ProcessingState::new(),
             s3_client: s3_client.cloned(),
         })
@@ -186,6 +207,28 @@ impl Processor {
     pub async fn process(&mut self) -> Result<ProcessingOutcome, ProcessorError> {
         tracing::info!("Starting processing with manifest: {}", self.manifest.name);
 
+        if let Some(base_ontology) = self.manifest.model.base_ontology.clone() {
+            let base_ontology = match base_ontology {
+                StorageLocation::Local { file_name, .. } => StorageLocation::Local {
+                    base_path: Some(self.base_path.clone()),
+                    file_name,
+                },
+                s3_location => s3_location,
+            };
+            tracing::info!("Importing base ontology: {}", base_ontology);
+            match self
+                .vocabulary_manager
+                .import_ontology(&base_ontology, self.s3_client.as_ref())
+                .await
+            {
+                Ok(state) => self.processing_state.merge(state),
+                Err(e) => self.processing_state.add_error(
+                    format!("Error importing base ontology: {}", e),
+                    Some("model_processing".to_string()),
+                ),
+            }
+        }
+
         tracing::info!("Processing model files...");
         let mut model_sequence = self.model_sequence();
         for step in model_sequence.drain(..) {
@@ -250,6 +293,12 @@ impl Processor {
             }
         }
 
+        // Resolve URI and Picklist references now that every instance has been loaded
+        let integrity_state = self.instance_manager.check_references();
+        self.processing_state.merge(integrity_state);
+        let cardinality_state = self.instance_manager.check_cardinality();
+        self.processing_state.merge(cardinality_state);
+
         if self.processing_state.has_errors() {
             return Ok(ProcessingOutcome::from_state(take(
                 &mut self.processing_state,
@@ -296,6 +345,21 @@ impl Processor {
             }
         }
 
+        if let Some(shapes_output_path) = &self.shapes_output_path {
+            if let Err(e) = self
+                .vocabulary_manager
+                .save_shapes(&vocabulary, shapes_output_path, self.s3_client.as_ref())
+                .await
+            {
+                self.processing_state.add_error(
+                    format!("Failed to save shapes: {}", e),
+                    Some("save_shapes".to_string()),
+                );
+            } else {
+                tracing::info!("Saved shapes to: {}", shapes_output_path);
+            }
+        }
+
         if let Err(e) = self
             .vocabulary_manager
             .save_vocabulary(vocabulary, &self.model_output_path, self.s3_client.as_ref())
@@ -406,6 +470,8 @@ impl Processor {
 
         let step = step.with_base_path(&self.base_path);
 
+        self.instance_manager.processor.begin_step(&step);
+
         // Process based on step type
         let result = match instance_step {
             InstanceStep::BasicInstanceStep | InstanceStep::PicklistStep => {
@@ -422,6 +488,18 @@ impl Processor {
                     .process_subclass_instance(&step, self.s3_client.as_ref())
                     .await
             }
+            InstanceStep::UnpivotInstanceStep => {
+                tracing::debug!("Processing as unpivoted property instance data");
+                self.instance_manager
+                    .process_unpivot_instance(&step, self.s3_client.as_ref())
+                    .await
+            }
+            InstanceStep::RelationshipInstanceStep => {
+                tracing::debug!("Processing as relationship instance data");
+                self.instance_manager
+                    .process_relationship_instance(&step, self.s3_client.as_ref())
+                    .await
+            }
             InstanceStep::PropertiesInstanceStep => {
                 tracing::debug!("Processing as properties instance data");
                 self.instance_manager
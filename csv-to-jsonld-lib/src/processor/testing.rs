@@ -0,0 +1,191 @@
+//! Runs a manifest end to end over files written to a temporary directory
+
+use serde_json::{json, Value as JsonValue};
+
+use super::ProcessorBuilder;
+use crate::error::{ProcessingMessage, ProcessingOutcome};
+use crate::Manifest;
+
+/// The columns every test model starts with
+const MODEL_COLUMNS: &str = "Class ID,Property ID,Class Name,Class Description,Property Name,Property Description,Type,Class Range";
+
+/// A model CSV with the standard columns followed by `extra_columns`; each row gives values for all of them
+pub(crate) fn model_csv(extra_columns: &[&str], rows: &str) -> String {
+    let mut header = MODEL_COLUMNS.to_string();
+    for column in extra_columns {
+        header.push(',');
+        header.push_str(column);
+    }
+    format!("{}\n{}", header, rows)
+}
+
+/// A manifest with a `model.csv` vocabulary step under `http://example.com/terms/`, to which
+/// tests add their own options and steps
+pub(crate) struct TestManifest(JsonValue);
+
+impl TestManifest {
+    pub fn new() -> Self {
+        Self(json!({
+            "@type": "CSVImportManifest", "@id": "http://example.com/test", "name": "Test",
+            "description": "",
+            "model": {
+                "baseIRI": "http://example.com/terms/",
+                "sequence": [{
+                    "path": "model.csv",
+                    "@type": ["CSVImportStep", "BasicVocabularyStep"]
+                }]
+            },
+            "instances": { "sequence": [] }
+        }))
+    }
+
+    /// Set a top-level manifest field
+    pub fn with(mut self, key: &str, value: JsonValue) -> Self {
+        self.0[key] = value;
+        self
+    }
+
+    /// Set a field of the model section
+    pub fn with_model(mut self, key: &str, value: JsonValue) -> Self {
+        self.0["model"][key] = value;
+        self
+    }
+
+    /// Set a field of the instances section
+    pub fn with_instances(mut self, key: &str, value: JsonValue) -> Self {
+        self.0["instances"][key] = value;
+        self
+    }
+
+    /// Add a vocabulary step after `model.csv`, with extra `fields`
+    pub fn model_step(mut self, path: &str, fields: JsonValue) -> Self {
+        let step = Self::step(path, "BasicVocabularyStep", fields);
+        self.0["model"]["sequence"]
+            .as_array_mut()
+            .unwrap()
+            .push(step);
+        self
+    }
+
+    /// Add an instance step of `step_type`, with extra `fields` such as `instanceType`
+    pub fn instance_step(mut self, path: &str, step_type: &str, fields: JsonValue) -> Self {
+        let step = Self::step(path, step_type, fields);
+        self.0["instances"]["sequence"]
+            .as_array_mut()
+            .unwrap()
+            .push(step);
+        self
+    }
+
+    pub fn build(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn step(path: &str, step_type: &str, fields: JsonValue) -> JsonValue {
+        let mut step = json!({ "path": path, "@type": ["CSVImportStep", step_type] });
+        for (key, value) in fields.as_object().unwrap() {
+            step[key] = value.clone();
+        }
+        step
+    }
+}
+
+pub(crate) struct ImportRun {
+    pub errors: Vec<ProcessingMessage>,
+    pub warnings: Vec<ProcessingMessage>,
+    /// The written outputs, or null when processing stopped before saving them
+    pub instances: JsonValue,
+    pub model: JsonValue,
+    pub shapes: JsonValue,
+}
+
+impl ImportRun {
+    /// The instance with the given (compacted) `@id`
+    pub fn instance(&self, id: &str) -> Option<&JsonValue> {
+        self.instances["insert"]
+            .as_array()?
+            .iter()
+            .find(|instance| instance["@id"] == id)
+    }
+
+    /// The model term with the given IRI, from the RDFS data model or an OWL ontology
+    pub fn term(&self, iri: &str) -> Option<&JsonValue> {
+        let insert = &self.model["insert"];
+        let terms: Vec<&JsonValue> = match insert.as_array() {
+            Some(terms) => terms.iter().collect(),
+            None => ["f:classes", "f:properties"]
+                .iter()
+                .filter_map(|key| insert[key].as_array())
+                .flatten()
+                .collect(),
+        };
+        terms.into_iter().find(|term| term["@id"] == iri)
+    }
+
+    pub fn has_error(&self, text: &str) -> bool {
+        self.errors.iter().any(|e| e.message.contains(text))
+    }
+
+    pub fn has_warning(&self, text: &str) -> bool {
+        self.warnings.iter().any(|w| w.message.contains(text))
+    }
+}
+
+/// Validate and process a manifest (JSON with comments) whose relative paths point at `files`
+pub(crate) async fn run_import(
+    manifest: &str,
+    files: &[(&str, &str)],
+    is_strict: bool,
+) -> ImportRun {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, contents) in files {
+        std::fs::write(dir.path().join(name), contents).unwrap();
+    }
+    let manifest_path = dir.path().join("manifest.jsonc");
+    std::fs::write(&manifest_path, manifest).unwrap();
+
+    let mut manifest = Manifest::from_file(&manifest_path).unwrap();
+    if let Err(state) = manifest.validate(is_strict) {
+        return ImportRun {
+            errors: state.get_errors().into_iter().cloned().collect(),
+            warnings: state.get_warnings().into_iter().cloned().collect(),
+            instances: JsonValue::Null,
+            model: JsonValue::Null,
+            shapes: JsonValue::Null,
+        };
+    }
+
+    let output = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+    let mut processor = ProcessorBuilder::from_manifest(manifest)
+        .with_base_path(dir.path())
+        .with_strict(is_strict)
+        .with_instance_output_path(output("instances.jsonld"))
+        .unwrap()
+        .with_model_output_path(output("model.jsonld"))
+        .unwrap()
+        .with_shapes_output_path(Some(output("shapes.jsonld").try_into().unwrap()))
+        .build()
+        .await
+        .unwrap();
+    let (errors, warnings) = match processor.process().await {
+        Ok(outcome) => outcome.take_messages(),
+        Err(e) => ProcessingOutcome::Failure {
+            errors: vec![e.into()],
+            warnings: vec![],
+        }
+        .take_messages(),
+    };
+
+    let read = |name: &str| {
+        std::fs::read_to_string(dir.path().join(name))
+            .map(|contents| serde_json::from_str(&contents).unwrap())
+            .unwrap_or(JsonValue::Null)
+    };
+    ImportRun {
+        errors,
+        warnings,
+        instances: read("instances.jsonld"),
+        model: read("model.jsonld"),
+        shapes: read("shapes.jsonld"),
+    }
+}
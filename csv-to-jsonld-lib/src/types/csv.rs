@@ -8,7 +8,7 @@ use std::{
     str::FromStr,
 };
 
-use super::VocabularyTerm;
+use super::{ValueConstraints, VocabularyTerm};
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
 #[allow(clippy::upper_case_acronyms)]
@@ -21,6 +21,15 @@ pub enum PropertyDatatype {
     Integer,
     Date,
     Boolean,
+    DateTime,
+    Time,
+    Duration,
+    GYear,
+    /// An alias of Integer: values are parsed the same way, as 64-bit integers, and only the
+    /// emitted datatype differs (xsd:long)
+    Long,
+    AnyURI,
+    LangString,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -34,6 +43,13 @@ pub enum StrictPropertyDatatype {
     Integer,
     Date,
     Boolean,
+    DateTime,
+    Time,
+    Duration,
+    GYear,
+    Long,
+    AnyURI,
+    LangString,
 }
 
 impl From<PropertyDatatype> for StrictPropertyDatatype {
@@ -49,6 +65,13 @@ impl From<PropertyDatatype> for StrictPropertyDatatype {
             PropertyDatatype::Integer => StrictPropertyDatatype::Integer,
             PropertyDatatype::Date => StrictPropertyDatatype::Date,
             PropertyDatatype::Boolean => StrictPropertyDatatype::Boolean,
+            PropertyDatatype::DateTime => StrictPropertyDatatype::DateTime,
+            PropertyDatatype::Time => StrictPropertyDatatype::Time,
+            PropertyDatatype::Duration => StrictPropertyDatatype::Duration,
+            PropertyDatatype::GYear => StrictPropertyDatatype::GYear,
+            PropertyDatatype::Long => StrictPropertyDatatype::Long,
+            PropertyDatatype::AnyURI => StrictPropertyDatatype::AnyURI,
+            PropertyDatatype::LangString => StrictPropertyDatatype::LangString,
         }
     }
 }
@@ -66,6 +89,13 @@ impl From<StrictPropertyDatatype> for PropertyDatatype {
             StrictPropertyDatatype::Integer => PropertyDatatype::Integer,
             StrictPropertyDatatype::Date => PropertyDatatype::Date,
             StrictPropertyDatatype::Boolean => PropertyDatatype::Boolean,
+            StrictPropertyDatatype::DateTime => PropertyDatatype::DateTime,
+            StrictPropertyDatatype::Time => PropertyDatatype::Time,
+            StrictPropertyDatatype::Duration => PropertyDatatype::Duration,
+            StrictPropertyDatatype::GYear => PropertyDatatype::GYear,
+            StrictPropertyDatatype::Long => PropertyDatatype::Long,
+            StrictPropertyDatatype::AnyURI => PropertyDatatype::AnyURI,
+            StrictPropertyDatatype::LangString => PropertyDatatype::LangString,
         }
     }
 }
@@ -74,17 +104,25 @@ impl FromStr for PropertyDatatype {
     type Err = ProcessorError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.trim().to_lowercase().as_str() {
+        let datatype = s.trim().to_lowercase();
+        match datatype.strip_prefix("xsd:").unwrap_or(&datatype) {
             "primary key identifier" | "@id" => Ok(PropertyDatatype::ID),
             "foreign key reference" | "uri" => Ok(PropertyDatatype::URI(None)),
             "picklist" => Ok(PropertyDatatype::Picklist(None)),
             "string" | "" => Ok(PropertyDatatype::String),
-            "float" => Ok(PropertyDatatype::Decimal),
+            "float" | "decimal" => Ok(PropertyDatatype::Decimal),
             "integer" => Ok(PropertyDatatype::Integer),
             "date/time" | "date" => Ok(PropertyDatatype::Date),
             "boolean" => Ok(PropertyDatatype::Boolean),
+            "datetime" | "timestamp" => Ok(PropertyDatatype::DateTime),
+            "time" => Ok(PropertyDatatype::Time),
+            "duration" => Ok(PropertyDatatype::Duration),
+            "gyear" | "year" => Ok(PropertyDatatype::GYear),
+            "long" => Ok(PropertyDatatype::Long),
+            "anyuri" | "url" => Ok(PropertyDatatype::AnyURI),
+            "langstring" | "rdf:langstring" => Ok(PropertyDatatype::LangString),
             _ => Err(ProcessorError::Processing(format!(
-                "Invalid CSV datatype: {} [Expected: @id, URI, String, Float, Integer, Date]",
+                "Invalid CSV datatype: {} [Expected: @id, URI, Picklist, String, Float, Decimal, Integer, Long, Date, DateTime, Time, Duration, gYear, Boolean, anyURI, langString]",
                 s.trim().to_lowercase().as_str()
             ))),
         }
@@ -104,7 +142,7 @@ impl<'de> Deserialize<'de> for PropertyDatatype {
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str(
-                    "a valid CSV datatype string: @id, URI, String, Float, Integer, Date",
+                    "a valid CSV datatype string: @id, URI, Picklist, String, Float, Decimal, Integer, Long, Date, DateTime, Time, Duration, gYear, Boolean, anyURI, langString",
                 )
             }
 
@@ -114,7 +152,7 @@ impl<'de> Deserialize<'de> for PropertyDatatype {
             {
                 PropertyDatatype::from_str(value).map_err(|_| {
                     E::custom(format!(
-                        "Invalid CSV datatype: {} [Expected: @id, URI, String, Float, Integer, Date]",
+                        "Invalid CSV datatype: {} [Expected: @id, URI, Picklist, String, Float, Decimal, Integer, Long, Date, DateTime, Time, Duration, gYear, Boolean, anyURI, langString]",
                         value.trim().to_lowercase().as_str()
                     ))
                 })
@@ -146,6 +184,13 @@ impl Serialize for PropertyDatatype {
             PropertyDatatype::Integer => "xsd:integer",
             PropertyDatatype::Date => "xsd:date",
             PropertyDatatype::Boolean => "xsd:boolean",
+            PropertyDatatype::DateTime => "xsd:dateTime",
+            PropertyDatatype::Time => "xsd:time",
+            PropertyDatatype::Duration => "xsd:duration",
+            PropertyDatatype::GYear => "xsd:gYear",
+            PropertyDatatype::Long => "xsd:long",
+            PropertyDatatype::AnyURI => "xsd:anyURI",
+            PropertyDatatype::LangString => "rdf:langString",
         };
         serializer.serialize_str(xsd_value)
     }
@@ -156,6 +201,13 @@ pub struct Header {
     pub name: String,
     pub datatype: PropertyDatatype,
     pub is_label_header: bool,
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// Language of the column's values, set for language-tagged string columns
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub constraints: ValueConstraints,
 }
 
 impl Default for Header {
@@ -164,6 +216,9 @@ impl Default for Header {
             name: String::new(),
             datatype: PropertyDatatype::String,
             is_label_header: false,
+            unit: None,
+            language: None,
+            constraints: ValueConstraints::default(),
         }
     }
 }
@@ -194,6 +249,9 @@ impl TryFrom<&VocabularyTerm> for Header {
             name,
             datatype: datatype.clone(),
             is_label_header: false,
+            unit: term.unit.clone(),
+            language: None,
+            constraints: term.constraints.clone(),
         })
     }
 }
@@ -212,6 +270,8 @@ pub enum OnEntity {
     Class,
     #[serde(rename = "PROPERTY")]
     Property,
+    #[serde(rename = "INSTANCE")]
+    Instance,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -221,11 +281,89 @@ pub struct PivotColumn {
     #[serde(rename = "newRelationshipProperty")]
     pub new_relationship_property: String,
     pub columns: Vec<String>,
+    #[serde(default, rename = "idTemplate")]
+    pub id_template: Option<String>,
+}
+
+/// Columns repeated with an index, such as `Item {i} Material` and `Item {i} Qty`. Each populated
+/// index becomes an instance of `instanceType`, linked from the row through
+/// `newRelationshipProperty`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RepeatingGroup {
+    #[serde(rename = "instanceType")]
+    pub instance_type: String,
+    #[serde(rename = "newRelationshipProperty")]
+    pub new_relationship_property: String,
+    pub columns: Vec<ColumnOverride>,
+    #[serde(default, rename = "idTemplate")]
+    pub id_template: Option<String>,
+}
+
+impl RepeatingGroup {
+    pub const INDEX_PLACEHOLDER: &'static str = "{i}";
+
+    /// The group's idTemplate with the index placeholder filled in
+    pub fn id_template_for(&self, index: &str) -> Option<String> {
+        self.id_template
+            .as_ref()
+            .map(|template| template.replace(Self::INDEX_PLACEHOLDER, index))
+    }
+}
+
+/// Resolves a URI-ranged column by matching its values against `lookupBy` on the already-loaded
+/// instances of the column's target class, instead of treating them as IDs
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Lookup {
+    pub column: String,
+    #[serde(rename = "lookupBy")]
+    pub lookup_by: String,
+}
+
+/// Columns that describe an entity referenced from the row, such as `Manufacturer Name` for the
+/// `Manufacturer` whose ID is in `has Manufacturer`. The referenced instance is created or merged
+/// with the mapped values
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ExtractedEntity {
+    #[serde(rename = "referenceColumn")]
+    pub reference_column: String,
+    #[serde(rename = "instanceType")]
+    pub instance_type: String,
+    pub columns: Vec<ColumnOverride>,
+}
+
+/// Groups the columns whose headers start with `{path}.` into a node object of `instanceType`,
+/// linked from the parent through `property`. A nested path containing dots (`address.geo`) is
+/// embedded in the object declared for its prefix (`address`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct NestedObject {
+    pub path: String,
+    #[serde(rename = "instanceType")]
+    pub instance_type: String,
+    pub property: String,
+    #[serde(default, rename = "idTemplate")]
+    pub id_template: Option<String>,
+    #[serde(default, rename = "blankNode")]
+    pub blank_node: bool,
+}
+
+impl NestedObject {
+    /// The property label a header refers to, if the header belongs to this nested path
+    pub fn property_label<'a>(&self, header: &'a str) -> Option<&'a str> {
+        header
+            .strip_prefix(self.path.as_str())
+            .and_then(|rest| rest.strip_prefix('.'))
+            .filter(|label| !label.is_empty())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ExtraItem {
+    #[serde(default)]
     pub column: String,
     #[serde(rename = "mapTo")]
     pub map_to: String,
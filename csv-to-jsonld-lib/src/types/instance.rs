@@ -19,6 +19,13 @@ pub struct JsonLdInstance {
 
 impl JsonLdInstance {
     pub fn update_with(&mut self, new_instance: JsonLdInstance) -> Result<(), ProcessorError> {
+        // Merge types, keeping the order they were first seen in
+        for type_ in new_instance.type_ {
+            if !self.type_.contains(&type_) {
+                self.type_.push(type_);
+            }
+        }
+
         // Merge properties
         for (key, value) in new_instance.properties {
             match self.properties.entry(key.clone()) {
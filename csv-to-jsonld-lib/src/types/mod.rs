@@ -1,10 +1,15 @@
 mod csv;
 mod instance;
+mod parse_profile;
 mod vocabulary;
 
-pub use csv::{ColumnOverride, ExtraItem, Header, OnEntity, PivotColumn, PropertyDatatype};
+pub use csv::{
+    ColumnOverride, ExtraItem, ExtractedEntity, Header, Lookup, NestedObject, OnEntity,
+    PivotColumn, PropertyDatatype, RepeatingGroup,
+};
 pub use instance::{JsonLdContext, JsonLdInstance, JsonLdInstances};
+pub use parse_profile::ParseProfile;
 pub use vocabulary::{
-    FlureeDataModel, IdOpt, JsonLdVocabulary, StrictIdOpt, StrictVocabularyMap, VocabularyMap,
-    VocabularyTerm,
+    FlureeDataModel, IdOpt, JsonLdVocabulary, StrictIdOpt, StrictVocabularyMap, ValueConstraints,
+    VocabularyMap, VocabularyTerm,
 };
@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::ProcessorError;
+use crate::utils::{DATE_FORMATS, DATE_TIME_FORMATS};
+use chrono::FixedOffset;
+
+/// Date formats that read the month before the day, left out for day-first locales
+const MONTH_FIRST_DATE_FORMATS: [&str; 2] = ["%m-%d-%Y", "%m/%d/%Y"];
+
+/// How dates, numbers and booleans are read. Profiles can be set on the manifest, on a step and
+/// on individual columns; each level only overrides the fields it sets
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ParseProfile {
+    /// BCP 47 locale (e.g. "en-US", "de-DE") supplying defaults for the other fields
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default, rename = "dateFormats")]
+    pub date_formats: Option<Vec<String>>,
+    #[serde(default, rename = "dateTimeFormats")]
+    pub date_time_formats: Option<Vec<String>>,
+    /// UTC offset (e.g. "+01:00", "Z") assumed for timestamps written without one. Without it,
+    /// such timestamps are kept without a time zone
+    #[serde(default, rename = "timeZone")]
+    pub time_zone: Option<String>,
+    /// Convert timestamps to UTC
+    #[serde(default, rename = "normalizeToUtc")]
+    pub normalize_to_utc: Option<bool>,
+    #[serde(default, rename = "decimalSeparator")]
+    pub decimal_separator: Option<String>,
+    #[serde(default, rename = "thousandsSeparator")]
+    pub thousands_separator: Option<String>,
+    #[serde(default, rename = "trueValues")]
+    pub true_values: Option<Vec<String>>,
+    #[serde(default, rename = "falseValues")]
+    pub false_values: Option<Vec<String>>,
+}
+
+/// Conventions of a locale: whether dates are written day-first, and its decimal and thousands
+/// separators
+struct LocaleDefaults {
+    day_first: bool,
+    decimal_separator: &'static str,
+    thousands_separator: &'static str,
+}
+
+fn locale_defaults(locale: &str) -> Option<LocaleDefaults> {
+    let locale = locale.replace('_', "-").to_lowercase();
+    let language = locale.split('-').next().unwrap_or_default();
+    let (day_first, decimal_separator, thousands_separator) = match (language, locale.as_str()) {
+        (_, "en-us" | "en-ph") | ("en", "en") => (false, ".", ","),
+        ("en", _) => (true, ".", ","),
+        ("de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr", _) => (true, ",", "."),
+        ("fr" | "sv" | "nb" | "no" | "fi" | "cs" | "pl" | "ru", _) => (true, ",", " "),
+        ("ja" | "zh" | "ko", _) => (false, ".", ","),
+        _ => return None,
+    };
+    Some(LocaleDefaults {
+        day_first,
+        decimal_separator,
+        thousands_separator,
+    })
+}
+
+/// Parse a UTC offset written as "Z", "UTC" or "+HH:MM"
+fn parse_offset(offset: &str) -> Option<FixedOffset> {
+    match offset.trim() {
+        "Z" | "z" | "UTC" | "utc" => FixedOffset::east_opt(0),
+        offset => offset.parse().ok(),
+    }
+}
+
+impl ParseProfile {
+    /// This profile with any fields it leaves unset taken from `base`
+    pub fn merged_over(&self, base: &ParseProfile) -> ParseProfile {
+        ParseProfile {
+            locale: self.locale.clone().or_else(|| base.locale.clone()),
+            date_formats: self
+                .date_formats
+                .clone()
+                .or_else(|| base.date_formats.clone()),
+            date_time_formats: self
+                .date_time_formats
+                .clone()
+                .or_else(|| base.date_time_formats.clone()),
+            time_zone: self.time_zone.clone().or_else(|| base.time_zone.clone()),
+            normalize_to_utc: self.normalize_to_utc.or(base.normalize_to_utc),
+            decimal_separator: self
+                .decimal_separator
+                .clone()
+                .or_else(|| base.decimal_separator.clone()),
+            thousands_separator: self
+                .thousands_separator
+                .clone()
+                .or_else(|| base.thousands_separator.clone()),
+            true_values: self
+                .true_values
+                .clone()
+                .or_else(|| base.true_values.clone()),
+            false_values: self
+                .false_values
+                .clone()
+                .or_else(|| base.false_values.clone()),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ProcessorError> {
+        if let Some(locale) = &self.locale {
+            if locale_defaults(locale).is_none() {
+                return Err(ProcessorError::InvalidManifest(format!(
+                    "Unsupported parse profile locale '{}'",
+                    locale
+                )));
+            }
+        }
+        if self.decimal_separator.is_some() && self.decimal_separator == self.thousands_separator {
+            return Err(ProcessorError::InvalidManifest(
+                "Parse profile decimalSeparator and thousandsSeparator must differ".into(),
+            ));
+        }
+        if let Some(time_zone) = &self.time_zone {
+            if parse_offset(time_zone).is_none() {
+                return Err(ProcessorError::InvalidManifest(format!(
+                    "Invalid parse profile timeZone '{}', expected a UTC offset such as \"+01:00\" or \"Z\"",
+                    time_zone
+                )));
+            }
+        }
+        for format in self
+            .date_formats
+            .iter()
+            .flatten()
+            .chain(self.date_time_formats.iter().flatten())
+        {
+            if chrono::format::StrftimeItems::new(format)
+                .any(|item| matches!(item, chrono::format::Item::Error))
+            {
+                return Err(ProcessorError::InvalidManifest(format!(
+                    "Invalid date format '{}' in parse profile",
+                    format
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The date formats to try, in order
+    pub fn date_formats(&self) -> Vec<&str> {
+        if let Some(formats) = &self.date_formats {
+            return formats.iter().map(String::as_str).collect();
+        }
+        let day_first = self
+            .locale
+            .as_deref()
+            .and_then(locale_defaults)
+            .is_some_and(|defaults| defaults.day_first);
+        DATE_FORMATS
+            .iter()
+            .copied()
+            .filter(|format| !day_first || !MONTH_FIRST_DATE_FORMATS.contains(format))
+            .collect()
+    }
+
+    /// The timestamp formats to try after RFC 3339, in order
+    pub fn date_time_formats(&self) -> Vec<&str> {
+        if let Some(formats) = &self.date_time_formats {
+            return formats.iter().map(String::as_str).collect();
+        }
+        let day_first = self
+            .locale
+            .as_deref()
+            .and_then(locale_defaults)
+            .is_some_and(|defaults| defaults.day_first);
+        DATE_TIME_FORMATS
+            .iter()
+            .copied()
+            .filter(|format| !day_first || !format.starts_with("%m/%d"))
+            .collect()
+    }
+
+    pub fn time_zone(&self) -> Option<FixedOffset> {
+        self.time_zone.as_deref().and_then(parse_offset)
+    }
+
+    pub fn normalize_to_utc(&self) -> bool {
+        self.normalize_to_utc.unwrap_or(false)
+    }
+
+    pub fn decimal_separator(&self) -> &str {
+        self.decimal_separator.as_deref().unwrap_or_else(|| {
+            self.locale
+                .as_deref()
+                .and_then(locale_defaults)
+                .map_or(".", |defaults| defaults.decimal_separator)
+        })
+    }
+
+    pub fn thousands_separator(&self) -> &str {
+        if let Some(thousands_separator) = &self.thousands_separator {
+            return thousands_separator;
+        }
+        let thousands_separator = self
+            .locale
+            .as_deref()
+            .and_then(locale_defaults)
+            .map_or(",", |defaults| defaults.thousands_separator);
+        // An explicit decimal separator takes precedence over the default thousands separator
+        if thousands_separator == self.decimal_separator() {
+            ""
+        } else {
+            thousands_separator
+        }
+    }
+
+    /// Normalize a number written with this profile's separators (and optional `$`/`%`) to the
+    /// form Rust parses
+    pub fn normalize_number(&self, value: &str) -> String {
+        let decimal_separator = self.decimal_separator();
+        value
+            .trim()
+            .replace(['$', '%'], "")
+            .replace(self.thousands_separator(), "")
+            .split(decimal_separator)
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Read a boolean token, case-insensitively
+    pub fn parse_bool(&self, value: &str) -> Option<bool> {
+        let value = value.trim().to_lowercase();
+        let matches = |tokens: &Option<Vec<String>>, defaults: &[&str]| match tokens {
+            Some(tokens) => tokens.iter().any(|t| t.to_lowercase() == value),
+            None => defaults.contains(&value.as_str()),
+        };
+        if matches(&self.true_values, &["true", "1", "yes"]) {
+            Some(true)
+        } else if matches(&self.false_values, &["false", "0", "no"]) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
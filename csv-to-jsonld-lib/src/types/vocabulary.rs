@@ -1,8 +1,12 @@
-use crate::utils::{are_conflicting, expand_iri_with_base, normalize_label_for_iri, to_camel_case};
+use crate::utils::{
+    are_conflicting, expand_iri_with_base, expand_prefixed_name, normalize_label_for_iri,
+    to_camel_case,
+};
 use crate::{error::ProcessorError, utils::to_pascal_case};
 use anyhow::Result;
 use serde::{Deserialize, Serialize, Serializer};
 use std::collections::hash_map::Entry;
+use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
 use std::{collections::HashMap, fmt::Display};
 
@@ -166,6 +170,22 @@ impl IdOpt {
         }
     }
 
+    /// The IRI of a prefixed name (e.g. "schema:name") whose prefix is declared
+    pub fn with_prefixes(&self, prefixes: &BTreeMap<String, String>) -> Option<Self> {
+        match self {
+            IdOpt::String(ref s) => expand_prefixed_name(prefixes, s).map(IdOpt::String),
+            IdOpt::ReplacementMap {
+                ref replacement_id,
+                ref original_id,
+            } => expand_prefixed_name(prefixes, replacement_id).map(|replacement_id| {
+                IdOpt::ReplacementMap {
+                    replacement_id,
+                    original_id: original_id.clone(),
+                }
+            }),
+        }
+    }
+
     pub fn without_base_iri(&self, base_iri: &str) -> Self {
         match self {
             IdOpt::String(ref s) => {
@@ -226,12 +246,41 @@ pub struct VocabularyTerm {
     pub label: Option<String>,
     pub sub_class_of: Option<Vec<String>>,
     pub comment: Option<String>,
+    /// Labels and comments by language tag, alongside the untagged label and comment
+    #[serde(default)]
+    pub localized_labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub localized_comments: BTreeMap<String, String>,
     pub domain: Option<Vec<String>>,
     pub range: Option<Vec<PropertyDatatype>>,
+    /// QUDT unit or currency IRI that numeric values of the property are measured in
+    pub unit: Option<String>,
+    /// Least and greatest number of values each instance may have for the property
+    pub min_count: Option<usize>,
+    pub max_count: Option<usize>,
+    #[serde(default)]
+    pub constraints: ValueConstraints,
     pub extra_items: HashMap<String, String>,
     pub one_of: Option<Vec<IdOpt>>,
 }
 
+/// Rules every value of a property must satisfy. Bounds are kept as written in the model (as
+/// canonical decimals) so they compare exactly
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ValueConstraints {
+    pub pattern: Option<String>,
+    pub min_value: Option<String>,
+    pub max_value: Option<String>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+}
+
+impl ValueConstraints {
+    pub fn is_empty(&self) -> bool {
+        self == &ValueConstraints::default()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StrictVocabularyTerm {
     pub id: StrictIdOpt,
@@ -239,8 +288,20 @@ pub struct StrictVocabularyTerm {
     pub label: Option<String>,
     pub sub_class_of: Option<Vec<String>>,
     pub comment: Option<String>,
+    #[serde(default)]
+    pub localized_labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub localized_comments: BTreeMap<String, String>,
     pub domain: Option<Vec<String>>,
     pub range: Option<Vec<StrictPropertyDatatype>>,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub min_count: Option<usize>,
+    #[serde(default)]
+    pub max_count: Option<usize>,
+    #[serde(default)]
+    pub constraints: ValueConstraints,
     pub extra_items: HashMap<String, String>,
     pub one_of: Option<Vec<StrictIdOpt>>,
 }
@@ -260,8 +321,14 @@ impl From<VocabularyTerm> for StrictVocabularyTerm {
             label: term.label,
             sub_class_of: term.sub_class_of,
             comment: term.comment,
+            localized_labels: term.localized_labels,
+            localized_comments: term.localized_comments,
             domain: term.domain,
             range,
+            unit: term.unit,
+            min_count: term.min_count,
+            max_count: term.max_count,
+            constraints: term.constraints,
             extra_items: term.extra_items,
             one_of,
         }
@@ -283,8 +350,14 @@ impl From<StrictVocabularyTerm> for VocabularyTerm {
             label: term.label,
             sub_class_of: term.sub_class_of,
             comment: term.comment,
+            localized_labels: term.localized_labels,
+            localized_comments: term.localized_comments,
             domain: term.domain,
             range,
+            unit: term.unit,
+            min_count: term.min_count,
+            max_count: term.max_count,
+            constraints: term.constraints,
             extra_items: term.extra_items,
             one_of,
         }
@@ -320,6 +393,19 @@ impl VocabularyTerm {
             )));
         }
 
+        merge_localized(
+            &mut self.localized_labels,
+            &other_entry.localized_labels,
+            &self.id,
+            "labels",
+        )?;
+        merge_localized(
+            &mut self.localized_comments,
+            &other_entry.localized_comments,
+            &self.id,
+            "comments",
+        )?;
+
         // Merge domains if different
         if let Some(domain) = &other_entry.domain {
             if let Some(self_domain) = &mut self.domain {
@@ -346,6 +432,51 @@ impl VocabularyTerm {
             }
         }
 
+        if are_conflicting(&self.unit, &other_entry.unit) {
+            return Err(ProcessorError::Processing(format!(
+                "The CSV uses conflicting units for the same term '{}':\n\
+                     - Unit 1: {}\n\
+                     - Unit 2: {}",
+                self.id,
+                self.unit.as_ref().unwrap(),
+                other_entry.unit.as_ref().unwrap()
+            )));
+        }
+
+        if self.unit.is_none() {
+            self.unit = other_entry.unit.clone();
+        }
+
+        if !self.constraints.is_empty()
+            && !other_entry.constraints.is_empty()
+            && self.constraints != other_entry.constraints
+        {
+            return Err(ProcessorError::Processing(format!(
+                "The CSV uses conflicting value constraints for the same term '{}': {:?} and {:?}",
+                self.id, self.constraints, other_entry.constraints
+            )));
+        }
+
+        if self.constraints.is_empty() {
+            self.constraints = other_entry.constraints.clone();
+        }
+
+        for (kind, count, other_count) in [
+            ("min count", &mut self.min_count, other_entry.min_count),
+            ("max count", &mut self.max_count, other_entry.max_count),
+        ] {
+            match (*count, other_count) {
+                (Some(existing), Some(other)) if existing != other => {
+                    return Err(ProcessorError::Processing(format!(
+                        "The CSV uses conflicting {} values for the same term '{}': {} and {}",
+                        kind, self.id, existing, other
+                    )));
+                }
+                (None, other) => *count = other,
+                _ => {}
+            }
+        }
+
         // Merge extra items
         // let extra_property_items = &other_entry.extra_items.iter().filter(|)
 
@@ -366,6 +497,89 @@ impl VocabularyTerm {
 
         Ok(())
     }
+
+    /// Fill in what a model CSV term leaves out from the imported ontology term it redefines. The
+    /// CSV values take precedence, and domains are combined
+    pub fn inherit(&mut self, imported: VocabularyTerm) {
+        let non_empty = |text: Option<String>| text.filter(|text| !text.is_empty());
+        self.label = non_empty(self.label.take()).or(imported.label);
+        self.comment = non_empty(self.comment.take()).or(imported.comment);
+        for (texts, imported_texts) in [
+            (&mut self.localized_labels, imported.localized_labels),
+            (&mut self.localized_comments, imported.localized_comments),
+        ] {
+            let csv_texts = std::mem::replace(texts, imported_texts);
+            texts.extend(csv_texts);
+        }
+        if self.sub_class_of.as_ref().is_none_or(Vec::is_empty) {
+            self.sub_class_of = imported.sub_class_of;
+        }
+        if let Some(imported_domain) = imported.domain {
+            let domain = self.domain.get_or_insert_with(Vec::new);
+            for class in imported_domain {
+                if !domain.contains(&class) {
+                    domain.push(class);
+                }
+            }
+        }
+        if self.range.as_ref().is_none_or(Vec::is_empty) {
+            self.range = imported.range;
+        }
+        self.unit = self.unit.take().or(imported.unit);
+        self.min_count = self.min_count.or(imported.min_count);
+        self.max_count = self.max_count.or(imported.max_count);
+        if self.constraints.is_empty() {
+            self.constraints = imported.constraints;
+        }
+        for (key, value) in imported.extra_items {
+            self.extra_items.entry(key).or_insert(value);
+        }
+        self.one_of = self.one_of.take().or(imported.one_of);
+    }
+}
+
+/// Merge per-language texts, treating different texts in the same language as a conflict
+fn merge_localized(
+    texts: &mut BTreeMap<String, String>,
+    other_texts: &BTreeMap<String, String>,
+    id: &IdOpt,
+    kind: &str,
+) -> Result<(), ProcessorError> {
+    for (language, text) in other_texts {
+        match texts.get(language) {
+            Some(existing) if existing != text => {
+                return Err(ProcessorError::Processing(format!(
+                    "The CSV uses conflicting {} in language '{}' for the same term '{}':\n\
+                         - Value 1: {}\n\
+                         - Value 2: {}",
+                    kind, language, id, existing, text
+                )));
+            }
+            Some(_) => {}
+            None => {
+                texts.insert(language.clone(), text.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// An untagged text on its own, or together with its language-tagged variants
+fn localized_value(
+    text: &Option<String>,
+    localized: &BTreeMap<String, String>,
+) -> Option<serde_json::Value> {
+    if localized.is_empty() {
+        return text.clone().map(serde_json::Value::String);
+    }
+    let values =
+        text.iter()
+            .map(|text| serde_json::Value::String(text.clone()))
+            .chain(localized.iter().map(
+                |(language, text)| serde_json::json!({ "@value": text, "@language": language }),
+            ))
+            .collect();
+    Some(serde_json::Value::Array(values))
 }
 
 impl Serialize for VocabularyTerm {
@@ -403,17 +617,11 @@ impl Serialize for VocabularyTerm {
                 ),
             );
         }
-        if let Some(label) = &self.label {
-            map.insert(
-                "rdfs:label".to_string(),
-                serde_json::Value::String(label.clone()),
-            );
+        if let Some(label) = localized_value(&self.label, &self.localized_labels) {
+            map.insert("rdfs:label".to_string(), label);
         }
-        if let Some(comment) = &self.comment {
-            map.insert(
-                "rdfs:comment".to_string(),
-                serde_json::Value::String(comment.clone()),
-            );
+        if let Some(comment) = localized_value(&self.comment, &self.localized_comments) {
+            map.insert("rdfs:comment".to_string(), comment);
         }
         if let Some(domain) = &self.domain {
             map.insert(
@@ -439,6 +647,12 @@ impl Serialize for VocabularyTerm {
                 ),
             );
         }
+        if let Some(unit) = &self.unit {
+            map.insert(
+                "qudt:unit".to_string(),
+                serde_json::Value::String(unit.clone()),
+            );
+        }
         for (key, value) in &self.extra_items {
             if !value.is_empty() {
                 map.insert(key.clone(), serde_json::Value::String(value.clone()));
@@ -598,7 +812,13 @@ impl VocabularyMap {
             Entry::Occupied(mut entry) => {
                 let term = entry.get_mut();
                 if let Some(one_of) = &mut term.one_of {
-                    one_of.push(instance_id);
+                    // Values may already be known from an imported ontology
+                    if !one_of
+                        .iter()
+                        .any(|value| value.final_iri() == instance_id.final_iri())
+                    {
+                        one_of.push(instance_id);
+                    }
                 } else {
                     term.one_of = Some(vec![instance_id]);
                 };
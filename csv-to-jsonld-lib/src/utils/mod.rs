@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 use crate::{types::PropertyDatatype, ProcessorError};
@@ -20,6 +21,27 @@ pub const DATE_FORMATS: [&str; 15] = [
     "%m-%Y",             // 06-2024 (assume the first day of the month)
 ];
 
+/// Timestamp formats tried after RFC 3339
+pub const DATE_TIME_FORMATS: [&str; 10] = [
+    "%Y-%m-%d %H:%M:%S%.f%:z", // 2024-06-17 12:30:00+02:00
+    "%Y-%m-%d %H:%M:%S%.f%z",  // 2024-06-17 12:30:00+0200
+    "%Y-%m-%dT%H:%M:%S%.f",    // 2024-06-17T12:30:00.000
+    "%Y-%m-%d %H:%M:%S%.f",    // 2024-06-17 12:30:00
+    "%Y-%m-%dT%H:%M",          // 2024-06-17T12:30
+    "%Y-%m-%d %H:%M",          // 2024-06-17 12:30
+    "%m/%d/%Y %H:%M:%S",       // 06/17/2024 12:30:00
+    "%m/%d/%Y %H:%M",          // 06/17/2024 12:30
+    "%d/%m/%Y %H:%M:%S",       // 17/06/2024 12:30:00
+    "%d/%m/%Y %H:%M",          // 17/06/2024 12:30
+];
+
+pub const TIME_FORMATS: [&str; 4] = [
+    "%H:%M:%S%.f", // 12:30:00.000
+    "%H:%M",       // 12:30
+    "%I:%M:%S %p", // 12:30:00 PM
+    "%I:%M %p",    // 12:30 PM
+];
+
 /// Convert a string to PascalCase
 pub fn to_pascal_case(s: &str) -> String {
     s.split(|c: char| !c.is_alphanumeric())
@@ -38,6 +60,179 @@ pub fn is_valid_url(url: &str) -> bool {
     url::Url::parse(url).is_ok()
 }
 
+/// Divide a canonical decimal by 100 by moving its decimal point, e.g. "34" to "0.34"
+pub fn percent_to_fraction(decimal: &str) -> String {
+    let (sign, unsigned) = match decimal.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", decimal),
+    };
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let integer = format!("{:0>3}", integer);
+    let (integer, hundreds) = integer.split_at(integer.len() - 2);
+    let integer = integer.trim_start_matches('0');
+    format!(
+        "{}{}.{}{}",
+        sign,
+        if integer.is_empty() { "0" } else { integer },
+        hundreds,
+        fraction
+    )
+}
+
+/// Whether a string is a well-formed BCP 47 language tag such as "de", "en-US" or "zh-Hant"
+pub fn is_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let primary_ok = subtags.next().is_some_and(|primary| {
+        (2..=8).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_alphabetic())
+    });
+    primary_ok
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+pub const QUDT_UNIT_BASE: &str = "http://qudt.org/vocab/unit/";
+pub const QUDT_CURRENCY_BASE: &str = "http://qudt.org/vocab/currency/";
+
+/// Resolve a unit or currency as written in a model or a value ("kg", "$", "USD", "%",
+/// "unit:KiloGM" or a full IRI) to its QUDT IRI
+pub fn resolve_unit(unit: &str) -> Option<String> {
+    let unit = unit.trim();
+    if unit.contains("://") {
+        return Some(unit.to_string());
+    }
+    if let Some(code) = unit.strip_prefix("unit:") {
+        return Some(format!("{}{}", QUDT_UNIT_BASE, code));
+    }
+    if let Some(code) = unit.strip_prefix("cur:") {
+        return Some(format!("{}{}", QUDT_CURRENCY_BASE, code));
+    }
+    let currency = match unit.to_lowercase().as_str() {
+        "$" | "usd" => Some("USD"),
+        "€" | "eur" => Some("EUR"),
+        "£" | "gbp" => Some("GBP"),
+        "¥" | "jpy" => Some("JPY"),
+        "chf" => Some("CHF"),
+        "cad" => Some("CAD"),
+        "aud" => Some("AUD"),
+        "cny" => Some("CNY"),
+        "inr" => Some("INR"),
+        _ => None,
+    };
+    if let Some(currency) = currency {
+        return Some(format!("{}{}", QUDT_CURRENCY_BASE, currency));
+    }
+    let code = match unit.to_lowercase().as_str() {
+        "%" | "percent" | "pct" => "PERCENT",
+        "kg" => "KiloGM",
+        "g" => "GM",
+        "mg" => "MilliGM",
+        "t" | "tonne" => "TONNE",
+        "lb" | "lbs" => "LB",
+        "oz" => "OZ",
+        "m" => "M",
+        "cm" => "CentiM",
+        "mm" => "MilliM",
+        "km" => "KiloM",
+        "in" => "IN",
+        "ft" => "FT",
+        "l" => "L",
+        "ml" => "MilliL",
+        "s" | "sec" => "SEC",
+        "min" => "MIN",
+        "h" | "hr" => "HR",
+        "day" | "days" => "DAY",
+        "v" => "V",
+        "a" => "A",
+        "w" => "W",
+        "kw" => "KiloW",
+        "hz" => "HZ",
+        "°c" | "degc" => "DEG_C",
+        "°f" | "degf" => "DEG_F",
+        _ => return None,
+    };
+    Some(format!("{}{}", QUDT_UNIT_BASE, code))
+}
+
+/// Split a value such as "$22.60", "12 kg" or "34%" into its number and the unit written before
+/// or after it. Returns None when units are written on both sides
+pub fn split_unit(value: &str) -> Option<(&str, Option<&str>)> {
+    let value = value.trim();
+    let (Some(start), Some(end)) = (
+        value.find(|c: char| c.is_ascii_digit() || "+-.".contains(c)),
+        value.rfind(|c: char| c.is_ascii_digit()),
+    ) else {
+        return Some((value, None));
+    };
+    let prefix = value[..start].trim();
+    let suffix = value[end + 1..].trim();
+    let number = value[start..end + 1].trim();
+    match (prefix.is_empty(), suffix.is_empty()) {
+        (true, true) => Some((number, None)),
+        (false, true) => Some((number, Some(prefix))),
+        (true, false) => Some((number, Some(suffix))),
+        (false, false) => None,
+    }
+}
+
+/// The xsd:decimal lexical form of a number such as "+1305.00" or ".5", keeping its digits
+/// exactly. Returns None for anything that is not a plain decimal (including exponents)
+pub fn canonical_decimal(value: &str) -> Option<String> {
+    let (sign, unsigned) = match value.strip_prefix(['+', '-']) {
+        Some(unsigned) if value.starts_with('-') => ("-", unsigned),
+        Some(unsigned) => ("", unsigned),
+        None => ("", value),
+    };
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if integer.is_empty() && fraction.is_empty()
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let integer = if integer.is_empty() { "0" } else { integer };
+    if fraction.is_empty() {
+        Some(format!("{}{}", sign, integer))
+    } else {
+        Some(format!("{}{}.{}", sign, integer, fraction))
+    }
+}
+
+/// Compare two decimals exactly, by sign, then integer digits, then fraction digits. Returns None
+/// unless both are plain decimals
+pub fn compare_decimals(a: &str, b: &str) -> Option<std::cmp::Ordering> {
+    /// Whether the decimal is negative, with its integer and fraction digits without padding zeros
+    fn parts(value: &str) -> Option<(bool, String, String)> {
+        let decimal = canonical_decimal(value.trim())?;
+        let (is_negative, unsigned) = match decimal.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned.to_string()),
+            None => (false, decimal),
+        };
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((&unsigned, ""));
+        let integer = integer.trim_start_matches('0').to_string();
+        let fraction = fraction.trim_end_matches('0').to_string();
+        // Negative zero is zero
+        let is_negative = is_negative && !(integer.is_empty() && fraction.is_empty());
+        Some((is_negative, integer, fraction))
+    }
+
+    let (a_negative, a_integer, a_fraction) = parts(a)?;
+    let (b_negative, b_integer, b_fraction) = parts(b)?;
+    let magnitude = a_integer
+        .len()
+        .cmp(&b_integer.len())
+        .then_with(|| a_integer.cmp(&b_integer))
+        .then_with(|| a_fraction.cmp(&b_fraction));
+    Some(match (a_negative, b_negative) {
+        (false, false) => magnitude,
+        (true, true) => magnitude.reverse(),
+        (false, true) => std::cmp::Ordering::Greater,
+        (true, false) => std::cmp::Ordering::Less,
+    })
+}
+
 /// Expands a relative IRI with a base IRI. Only expands if the relative IRI does not already start with a scheme
 pub fn expand_iri_with_base(base_iri: &str, possibly_relative_iri: &str) -> String {
     // Attempt to parse the base IRI
@@ -73,6 +268,35 @@ pub fn expand_iri_with_base(base_iri: &str, possibly_relative_iri: &str) -> Stri
     // urlencoding::decode(result)
 }
 
+/// Expands a prefixed name (e.g. "schema:name") whose prefix is declared. Full IRIs and names
+/// with undeclared prefixes return None
+pub fn expand_prefixed_name(prefixes: &BTreeMap<String, String>, name: &str) -> Option<String> {
+    let (prefix, local_name) = name.trim().split_once(':')?;
+    if local_name.starts_with("//") {
+        return None;
+    }
+    prefixes
+        .get(prefix)
+        .map(|namespace| format!("{}{}", namespace, local_name))
+}
+
+/// The prefix of a name written like a prefixed name ("skos:prefLabel") whose prefix is not
+/// declared. Full IRIs and names with spaces, such as "Ratio: A/B", are not prefixed names
+pub fn undeclared_prefix<'a>(
+    prefixes: &BTreeMap<String, String>,
+    name: &'a str,
+) -> Option<&'a str> {
+    let (prefix, local_name) = name.trim().split_once(':')?;
+    let is_prefixed_name = prefix.starts_with(|c: char| c.is_ascii_alphabetic())
+        && prefix
+            .chars()
+            .all(|c| c.is_alphanumeric() || "_-.".contains(c))
+        && !local_name.is_empty()
+        && !local_name.starts_with("//")
+        && !local_name.contains(char::is_whitespace);
+    (is_prefixed_name && !prefixes.contains_key(prefix)).then_some(prefix)
+}
+
 /// Normalize a string to be used as an IRI label
 pub fn normalize_label_for_iri(label: &str) -> String {
     label
@@ -180,6 +404,76 @@ pub fn are_conflicting(value_one: &Option<String>, value_two: &Option<String>) -
 //     Ok(())
 // }
 
+/// Extract the column names referenced by `{Column Name}` placeholders in an ID template
+pub fn template_placeholders(template: &str) -> Result<Vec<String>, ProcessorError> {
+    let mut placeholders = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if rest[..start].contains('}') {
+            return Err(ProcessorError::InvalidManifest(format!(
+                "Unopened placeholder in idTemplate: {}",
+                template
+            )));
+        }
+        let after_start = &rest[start + 1..];
+        let end = after_start.find('}').ok_or_else(|| {
+            ProcessorError::InvalidManifest(format!(
+                "Unclosed placeholder in idTemplate: {}",
+                template
+            ))
+        })?;
+        let column = &after_start[..end];
+        if column.is_empty() || column.contains('{') {
+            return Err(ProcessorError::InvalidManifest(format!(
+                "Invalid placeholder in idTemplate: {}",
+                template
+            )));
+        }
+        placeholders.push(column.to_string());
+        rest = &after_start[end + 1..];
+    }
+    if rest.contains('}') {
+        return Err(ProcessorError::InvalidManifest(format!(
+            "Unopened placeholder in idTemplate: {}",
+            template
+        )));
+    }
+    if placeholders.is_empty() {
+        return Err(ProcessorError::InvalidManifest(format!(
+            "idTemplate must reference at least one column: {}",
+            template
+        )));
+    }
+    Ok(placeholders)
+}
+
+/// Render an ID template, percent-encoding each substituted value so the result is IRI-safe
+pub fn render_id_template<'a, F>(template: &str, mut lookup: F) -> Result<String, ProcessorError>
+where
+    F: FnMut(&str) -> Option<&'a str>,
+{
+    let mut result = String::new();
+    let mut rest = template;
+    for column in template_placeholders(template)? {
+        let placeholder = format!("{{{}}}", column);
+        let start = rest.find(&placeholder).unwrap();
+        result.push_str(&rest[..start]);
+        let value = lookup(&column)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| {
+                ProcessorError::Processing(format!(
+                    "Missing or empty value for column '{}' referenced by idTemplate",
+                    column
+                ))
+            })?;
+        result.push_str(&urlencoding::encode(value));
+        rest = &rest[start + placeholder.len()..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
 pub fn validate_column_identifier(term: String) -> Result<String, ProcessorError> {
     if let Some(rest) = term.strip_prefix('$') {
         Ok(rest.to_string())
@@ -276,4 +570,121 @@ mod tests {
             "http://example.com/base#path-to/name with spaces"
         );
     }
+
+    #[test]
+    fn test_canonical_decimal() {
+        assert_eq!(super::canonical_decimal("1305.00").unwrap(), "1305.00");
+        assert_eq!(super::canonical_decimal("+.5").unwrap(), "0.5");
+        assert_eq!(super::canonical_decimal("-12.").unwrap(), "-12");
+        assert!(super::canonical_decimal("NaN").is_none());
+        assert!(super::canonical_decimal("1e5").is_none());
+        assert!(super::canonical_decimal(".").is_none());
+        assert!(super::canonical_decimal("1.2.3").is_none());
+        assert_eq!(
+            super::compare_decimals("0.30000000000000001", "0.3"),
+            Some(std::cmp::Ordering::Greater)
+        );
+        assert_eq!(
+            super::compare_decimals("-010.50", "-10.5"),
+            Some(std::cmp::Ordering::Equal)
+        );
+        assert_eq!(
+            super::compare_decimals("-0.0", "0"),
+            Some(std::cmp::Ordering::Equal)
+        );
+        assert_eq!(
+            super::compare_decimals("-2", "-10"),
+            Some(std::cmp::Ordering::Greater)
+        );
+        assert_eq!(
+            super::compare_decimals("9.99", "10"),
+            Some(std::cmp::Ordering::Less)
+        );
+        assert!(super::compare_decimals("inf", "1").is_none());
+        assert_eq!(super::percent_to_fraction("34"), "0.34");
+        assert_eq!(super::percent_to_fraction("-5.5"), "-0.055");
+        assert_eq!(super::percent_to_fraction("120"), "1.20");
+    }
+
+    #[test]
+    fn test_is_language_tag() {
+        assert!(super::is_language_tag("de"));
+        assert!(super::is_language_tag("en-US"));
+        assert!(super::is_language_tag("zh-Hant-TW"));
+        assert!(!super::is_language_tag("example.com"));
+        assert!(!super::is_language_tag("d"));
+        assert!(!super::is_language_tag("en--US"));
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(
+            super::split_unit("$1,305.00"),
+            Some(("1,305.00", Some("$")))
+        );
+        assert_eq!(super::split_unit("12 kg"), Some(("12", Some("kg"))));
+        assert_eq!(super::split_unit("-34%"), Some(("-34", Some("%"))));
+        assert_eq!(super::split_unit("42"), Some(("42", None)));
+        assert_eq!(super::split_unit("$12 USD"), None);
+        assert_eq!(
+            super::resolve_unit("$").unwrap(),
+            "http://qudt.org/vocab/currency/USD"
+        );
+        assert_eq!(
+            super::resolve_unit("unit:KiloGM"),
+            super::resolve_unit("KG")
+        );
+        assert!(super::resolve_unit("furlongs").is_none());
+    }
+
+    #[test]
+    fn test_render_id_template() {
+        let template = "{Warehouse Location}/{has Material}";
+        let rendered = super::render_id_template(template, |column| match column {
+            "Warehouse Location" => Some("9815 Mayflower Park Dr, Carmel"),
+            "has Material" => Some("135"),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(rendered, "9815%20Mayflower%20Park%20Dr%2C%20Carmel/135");
+
+        let missing = super::render_id_template(template, |column| match column {
+            "Warehouse Location" => Some("Carmel"),
+            _ => Some(" "),
+        });
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_template_placeholders() {
+        assert_eq!(
+            super::template_placeholders("inv-{A}-{B C}").unwrap(),
+            vec!["A".to_string(), "B C".to_string()]
+        );
+        assert!(super::template_placeholders("no-placeholders").is_err());
+        assert!(super::template_placeholders("{unclosed").is_err());
+        assert!(super::template_placeholders("unopened}").is_err());
+    }
+
+    #[test]
+    fn test_expand_prefixed_name() {
+        let prefixes = std::collections::BTreeMap::from([(
+            "schema".to_string(),
+            "https://schema.org/".to_string(),
+        )]);
+        assert_eq!(
+            super::expand_prefixed_name(&prefixes, "schema:name").as_deref(),
+            Some("https://schema.org/name")
+        );
+        assert!(super::expand_prefixed_name(&prefixes, "skos:prefLabel").is_none());
+        assert!(super::expand_prefixed_name(&prefixes, "schema://name").is_none());
+        assert!(super::expand_prefixed_name(&prefixes, "Product Name").is_none());
+        assert_eq!(
+            super::undeclared_prefix(&prefixes, "skos:prefLabel"),
+            Some("skos")
+        );
+        assert!(super::undeclared_prefix(&prefixes, "schema:name").is_none());
+        assert!(super::undeclared_prefix(&prefixes, "http://example.com/a").is_none());
+        assert!(super::undeclared_prefix(&prefixes, "Ratio: A/B").is_none());
+    }
 }
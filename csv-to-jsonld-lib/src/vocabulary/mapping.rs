@@ -5,7 +5,33 @@ use std::collections::HashMap;
 use crate::error::{ProcessingState, ProcessorError};
 use crate::manifest::{ModelStep, StepType};
 use crate::types::{ColumnOverride, ExtraItem, IdOpt};
-use crate::utils::validate_column_identifier;
+use crate::utils::{is_language_tag, validate_column_identifier};
+
+/// The text of a term that a per-language model column, such as "Class Name (fr)", provides
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub enum LocalizedText {
+    ClassLabel,
+    ClassComment,
+    PropertyLabel,
+    PropertyComment,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalizedColumn {
+    pub column: String,
+    pub language: String,
+    pub text: LocalizedText,
+}
+
+/// Cells of the optional value constraint columns for a property row
+#[derive(Debug, Default)]
+pub struct ConstraintCells<'a> {
+    pub pattern: Option<&'a str>,
+    pub min_value: Option<&'a str>,
+    pub max_value: Option<&'a str>,
+    pub min_length: Option<&'a str>,
+    pub max_length: Option<&'a str>,
+}
 
 #[derive(Debug)]
 pub struct RowValues<'a> {
@@ -17,6 +43,13 @@ pub struct RowValues<'a> {
     pub property_description: Option<&'a str>,
     pub property_type: Option<&'a str>,
     pub property_class: Option<&'a str>,
+    pub property_unit: Option<&'a str>,
+    pub property_required: Option<&'a str>,
+    pub property_min_count: Option<&'a str>,
+    pub property_max_count: Option<&'a str>,
+    pub property_constraints: ConstraintCells<'a>,
+    /// Per-language labels and comments, as (text, language, value)
+    pub localized: Vec<(LocalizedText, String, &'a str)>,
     pub extra_items: HashMap<String, ExtraItem>,
 }
 
@@ -120,6 +153,12 @@ impl MappingConfig {
                     property_description: None,
                     property_type: None,
                     property_class: None,
+                    property_unit: None,
+                    property_required: None,
+                    property_min_count: None,
+                    property_max_count: None,
+                    property_constraints: ConstraintCells::default(),
+                    localized: mapping.get_localized_values(record, headers),
                     extra_items,
                 })
             }
@@ -158,6 +197,7 @@ impl MappingConfig {
                     .unwrap_or(&"".to_string()),
             )
             .unwrap_or("");
+        let property_unit = mapping.get_optional_value(record, headers, &mapping.unit_column);
         let result = RowValues {
             class_id,
             class_name: Some(class_name),
@@ -167,6 +207,24 @@ impl MappingConfig {
             property_description: Some(property_desc),
             property_type: Some(property_type),
             property_class: Some(property_class),
+            property_unit,
+            property_required: mapping.get_optional_value(
+                record,
+                headers,
+                &mapping.required_column,
+            ),
+            property_min_count: mapping.get_optional_value(
+                record,
+                headers,
+                &mapping.min_count_column,
+            ),
+            property_max_count: mapping.get_optional_value(
+                record,
+                headers,
+                &mapping.max_count_column,
+            ),
+            property_constraints: mapping.get_constraint_cells(record, headers),
+            localized: mapping.get_localized_values(record, headers),
             extra_items,
         };
         Ok(result)
@@ -217,6 +275,12 @@ impl MappingConfig {
             property_description: None,
             property_type: None,
             property_class: None,
+            property_unit: None,
+            property_required: None,
+            property_min_count: None,
+            property_max_count: None,
+            property_constraints: ConstraintCells::default(),
+            localized: mapping.get_localized_values(record, headers),
             extra_items,
         };
         Ok(result)
@@ -312,6 +376,7 @@ impl MappingConfig {
                 (extra_item.map_to.clone(), extra_item)
             })
             .collect::<HashMap<_, _>>();
+        let property_unit = mapping.get_optional_value(record, headers, &mapping.unit_column);
         let result = RowValues {
             class_id,
             class_name: None,
@@ -321,6 +386,24 @@ impl MappingConfig {
             property_description: Some(property_desc),
             property_type: Some(property_type),
             property_class: Some(property_class),
+            property_unit,
+            property_required: mapping.get_optional_value(
+                record,
+                headers,
+                &mapping.required_column,
+            ),
+            property_min_count: mapping.get_optional_value(
+                record,
+                headers,
+                &mapping.min_count_column,
+            ),
+            property_max_count: mapping.get_optional_value(
+                record,
+                headers,
+                &mapping.max_count_column,
+            ),
+            property_constraints: mapping.get_constraint_cells(record, headers),
+            localized: mapping.get_localized_values(record, headers),
             extra_items,
         };
         Ok(result)
@@ -344,6 +427,26 @@ pub struct VocabularyColumnMapping {
     pub type_column: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub property_class_column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_count_column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_count_column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern_column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_value_column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_value_column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length_column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length_column: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub localized_columns: Vec<LocalizedColumn>,
     pub extra_items: HashMap<String, ExtraItem>,
 }
 
@@ -358,6 +461,16 @@ impl VocabularyColumnMapping {
             property_description_column: Some("Property Description".to_string()),
             type_column: Some("Type".to_string()),
             property_class_column: Some("Class Range".to_string()),
+            unit_column: Some("Unit".to_string()),
+            required_column: Some("Required".to_string()),
+            min_count_column: Some("Min Count".to_string()),
+            max_count_column: Some("Max Count".to_string()),
+            pattern_column: Some("Pattern".to_string()),
+            min_value_column: Some("Min Value".to_string()),
+            max_value_column: Some("Max Value".to_string()),
+            min_length_column: Some("Min Length".to_string()),
+            max_length_column: Some("Max Length".to_string()),
+            localized_columns: vec![],
             extra_items: HashMap::new(),
         }
     }
@@ -372,6 +485,16 @@ impl VocabularyColumnMapping {
             property_description_column: None,
             type_column: None,
             property_class_column: None,
+            unit_column: None,
+            required_column: None,
+            min_count_column: None,
+            max_count_column: None,
+            pattern_column: None,
+            min_value_column: None,
+            max_value_column: None,
+            min_length_column: None,
+            max_length_column: None,
+            localized_columns: vec![],
             extra_items: HashMap::new(),
         }
     }
@@ -386,6 +509,16 @@ impl VocabularyColumnMapping {
             property_description_column: Some("Property Description".to_string()),
             type_column: Some("Type".to_string()),
             property_class_column: Some("Class Range".to_string()),
+            unit_column: Some("Unit".to_string()),
+            required_column: Some("Required".to_string()),
+            min_count_column: Some("Min Count".to_string()),
+            max_count_column: Some("Max Count".to_string()),
+            pattern_column: Some("Pattern".to_string()),
+            min_value_column: Some("Min Value".to_string()),
+            max_value_column: Some("Max Value".to_string()),
+            min_length_column: Some("Min Length".to_string()),
+            max_length_column: Some("Max Length".to_string()),
+            localized_columns: vec![],
             extra_items: HashMap::new(),
         }
     }
@@ -500,9 +633,18 @@ impl VocabularyColumnMapping {
                 "Property.TargetClass" => {
                     self.property_class_column = Some(override_.column.clone())
                 }
+                "Property.Unit" => self.unit_column = Some(override_.column.clone()),
+                "Property.Required" => self.required_column = Some(override_.column.clone()),
+                "Property.MinCount" => self.min_count_column = Some(override_.column.clone()),
+                "Property.MaxCount" => self.max_count_column = Some(override_.column.clone()),
+                "Property.Pattern" => self.pattern_column = Some(override_.column.clone()),
+                "Property.MinValue" => self.min_value_column = Some(override_.column.clone()),
+                "Property.MaxValue" => self.max_value_column = Some(override_.column.clone()),
+                "Property.MinLength" => self.min_length_column = Some(override_.column.clone()),
+                "Property.MaxLength" => self.max_length_column = Some(override_.column.clone()),
                 _ => {
                     return Err(ProcessorError::InvalidManifest(format!(
-                    "Invalid override mapTo value for BasicVocabularyStep: {}. Overrides must be one of the following: Class.ID, Class.Description, Property.ID, Property.Description, Property.Type, Property.TargetClass. If you want to specify an extraItem, use the extraItems field in the manifest",
+                    "Invalid override mapTo value for BasicVocabularyStep: {}. Overrides must be one of the following: Class.ID, Class.Description, Property.ID, Property.Description, Property.Type, Property.TargetClass, Property.Unit, Property.Required, Property.MinCount, Property.MaxCount, Property.Pattern, Property.MinValue, Property.MaxValue, Property.MinLength, Property.MaxLength. If you want to specify an extraItem, use the extraItems field in the manifest",
                     override_.map_to
                 )));
                 }
@@ -544,6 +686,84 @@ impl VocabularyColumnMapping {
             .and_then(|i| record.get(i))
     }
 
+    /// Find per-language variants of the label and description columns, written as the column
+    /// name followed by a language tag in parentheses, e.g. "Class Name (fr)"
+    pub fn detect_localized_columns(&mut self, headers: &csv::StringRecord) {
+        for header in headers {
+            let Some((base, language)) = header
+                .strip_suffix(')')
+                .and_then(|header| header.rsplit_once(" ("))
+                .filter(|(_, language)| is_language_tag(language))
+            else {
+                continue;
+            };
+            let text = [
+                (&self.class_label_column, LocalizedText::ClassLabel),
+                (&self.class_description_column, LocalizedText::ClassComment),
+                (&self.property_name_column, LocalizedText::PropertyLabel),
+                (
+                    &self.property_description_column,
+                    LocalizedText::PropertyComment,
+                ),
+            ]
+            .into_iter()
+            .find(|(column, _)| column.as_deref() == Some(base))
+            .map(|(_, text)| text);
+            if let Some(text) = text {
+                self.localized_columns.push(LocalizedColumn {
+                    column: header.to_string(),
+                    language: language.to_string(),
+                    text,
+                });
+            }
+        }
+    }
+
+    fn get_localized_values<'a>(
+        &self,
+        record: &'a csv::StringRecord,
+        headers: &csv::StringRecord,
+    ) -> Vec<(LocalizedText, String, &'a str)> {
+        self.localized_columns
+            .iter()
+            .filter_map(|localized| {
+                self.get_value(record, headers, &localized.column)
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(|value| (localized.text, localized.language.clone(), value))
+            })
+            .collect()
+    }
+
+    fn get_constraint_cells<'a>(
+        &self,
+        record: &'a csv::StringRecord,
+        headers: &csv::StringRecord,
+    ) -> ConstraintCells<'a> {
+        ConstraintCells {
+            pattern: self.get_optional_value(record, headers, &self.pattern_column),
+            min_value: self.get_optional_value(record, headers, &self.min_value_column),
+            max_value: self.get_optional_value(record, headers, &self.max_value_column),
+            min_length: self.get_optional_value(record, headers, &self.min_length_column),
+            max_length: self.get_optional_value(record, headers, &self.max_length_column),
+        }
+    }
+
+    /// The value of an optional model column such as the unit, if the model has the column and
+    /// it is filled in
+    fn get_optional_value<'a>(
+        &self,
+        record: &'a csv::StringRecord,
+        headers: &csv::StringRecord,
+        column: &Option<String>,
+    ) -> Option<&'a str> {
+        column
+            .as_ref()
+            .and_then(|column| self.get_value(record, headers, column))
+            .map(str::trim)
+            .filter(|unit| !unit.is_empty())
+    }
+
     pub fn get_id_value(
         &self,
         record: &csv::StringRecord,
@@ -653,3 +873,56 @@ impl VocabularyColumnMapping {
         Ok(processing_state)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_localized_labels_and_comments() {
+        let model = model_csv(
+            &[
+                "Class Name (fr)",
+                "Class Description (de)",
+                "Property Name (fr)",
+            ],
+            "\
+Product,Product ID,Product,A product,Product ID,Identifier,@id,,Produit,Ein Produkt,Identifiant
+Product,Weight,Product,A product,Weight,Weight in kg,Decimal,,,Ein Produkt,Poids
+",
+        );
+        let run = run_import(&TestManifest::new().build(), &[("model.csv", &model)], true).await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let product = run.term("http://example.com/terms/Product").unwrap();
+        assert_eq!(
+            product["rdfs:label"],
+            json!(["Product", { "@value": "Produit", "@language": "fr" }])
+        );
+        assert_eq!(
+            product["rdfs:comment"],
+            json!(["A product", { "@value": "Ein Produkt", "@language": "de" }])
+        );
+        let weight = run.term("http://example.com/terms/weight").unwrap();
+        assert_eq!(
+            weight["rdfs:label"],
+            json!(["Weight", { "@value": "Poids", "@language": "fr" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_localized_label_conflicts_are_per_language() {
+        let model = model_csv(
+            &["Class Name (fr)", "Class Name (de)"],
+            "\
+Product,Product ID,Product,A product,Product ID,Identifier,@id,,Produit,Produkt
+Product,Weight,Product,A product,Weight,Weight in kg,Decimal,,Article,Produkt
+",
+        );
+        let run = run_import(&TestManifest::new().build(), &[("model.csv", &model)], true).await;
+
+        assert!(run.has_error("conflicting labels in language 'fr'"));
+        assert!(!run.has_error("language 'de'"));
+    }
+}
@@ -1,6 +1,9 @@
 mod mapping;
+mod ontology;
+mod owl;
 mod processor;
 mod serializer;
+mod shapes;
 
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -25,7 +28,8 @@ impl VocabularyManager {
         vocab_meta_path: Option<StorageLocation>,
         s3_client: Option<&aws_sdk_s3::Client>,
     ) -> Result<Self, ProcessorError> {
-        let is_manifest_vocab_empty = manifest.model.sequence.is_empty();
+        let is_manifest_vocab_empty =
+            manifest.model.sequence.is_empty() && manifest.model.base_ontology.is_none();
         let processor = if is_manifest_vocab_empty {
             if let Some(vocab_meta_path) = vocab_meta_path {
                 tracing::info!(
@@ -72,6 +76,14 @@ impl VocabularyManager {
     //         .await
     // }
 
+    pub async fn import_ontology(
+        &mut self,
+        path: &StorageLocation,
+        s3_client: Option<&aws_sdk_s3::Client>,
+    ) -> Result<ProcessingState, ProcessorError> {
+        self.processor.import_ontology(path, s3_client).await
+    }
+
     pub async fn save_vocabulary(
         &self,
         vocabulary: VocabularyMap,
@@ -83,6 +95,17 @@ impl VocabularyManager {
             .await
     }
 
+    pub async fn save_shapes(
+        &self,
+        vocabulary: &VocabularyMap,
+        output_path: &StorageLocation,
+        s3_client: Option<&aws_sdk_s3::Client>,
+    ) -> Result<(), ProcessorError> {
+        self.serializer
+            .save_shapes(vocabulary, output_path, s3_client)
+            .await
+    }
+
     pub async fn save_vocabulary_meta(
         &mut self,
         vocabulary: &VocabularyMap,
@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::{Graph, Term, RDF, XSD};
+use crate::error::ProcessorError;
+
+/// Parse a JSON-LD document into triples. Contexts must be inline; a Fluree transaction's
+/// `insert` is read as the document's graph. Remote contexts, reverse properties, included and
+/// nested nodes, and containers other than `@list` and `@set` are rejected
+pub(super) fn parse(contents: &str) -> Result<Graph, ProcessorError> {
+    let document: JsonValue = serde_json::from_str(contents).map_err(|e| {
+        ProcessorError::Processing(format!("Failed to parse JSON-LD ontology: {}", e))
+    })?;
+    let mut reader = JsonLdReader {
+        graph: Graph::default(),
+    };
+    reader.document(&document, &Context::default())?;
+    Ok(reader.graph)
+}
+
+#[derive(Debug, Clone, Default)]
+struct Context {
+    terms: HashMap<String, TermDefinition>,
+    vocab: Option<String>,
+    base: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TermDefinition {
+    id: String,
+    /// Whether string values are IRIs ("@type": "@id" or "@vocab")
+    is_reference: bool,
+    datatype: Option<String>,
+    is_list: bool,
+}
+
+impl Context {
+    fn with(&self, local: &JsonValue) -> Result<Context, ProcessorError> {
+        let mut context = self.clone();
+        match local {
+            JsonValue::Array(contexts) => {
+                for local in contexts {
+                    context = context.with(local)?;
+                }
+            }
+            JsonValue::Object(definitions) => {
+                if let Some(vocab) = definitions.get("@vocab").and_then(JsonValue::as_str) {
+                    context.vocab = Some(context.expand(vocab, true));
+                }
+                if let Some(base) = definitions.get("@base").and_then(JsonValue::as_str) {
+                    context.base = Some(base.to_string());
+                }
+                // Prefixes first, so later definitions can use them
+                for (term, definition) in definitions {
+                    if let JsonValue::String(id) = definition {
+                        if !term.starts_with('@') {
+                            let id = context.expand(id, true);
+                            context.terms.insert(
+                                term.clone(),
+                                TermDefinition {
+                                    id,
+                                    ..Default::default()
+                                },
+                            );
+                        }
+                    }
+                }
+                if definitions.contains_key("@import") {
+                    return Err(unsupported("@import"));
+                }
+                for (term, definition) in definitions {
+                    if let JsonValue::Object(definition) = definition {
+                        if definition.contains_key("@reverse") {
+                            return Err(unsupported("@reverse"));
+                        }
+                        match definition.get("@container").and_then(JsonValue::as_str) {
+                            None | Some("@list" | "@set") => {}
+                            Some(container) => return Err(unsupported(container)),
+                        }
+                        let id = definition
+                            .get("@id")
+                            .and_then(JsonValue::as_str)
+                            .unwrap_or(term);
+                        let type_ = definition.get("@type").and_then(JsonValue::as_str);
+                        let definition = TermDefinition {
+                            id: context.expand(id, true),
+                            is_reference: matches!(type_, Some("@id" | "@vocab")),
+                            datatype: type_
+                                .filter(|t| !t.starts_with('@'))
+                                .map(|t| context.expand(t, true)),
+                            is_list: definition.get("@container").and_then(JsonValue::as_str)
+                                == Some("@list"),
+                        };
+                        context.terms.insert(term.clone(), definition);
+                    }
+                }
+            }
+            JsonValue::Null => return Ok(Context::default()),
+            _ => {
+                return Err(ProcessorError::Processing(
+                    "Remote JSON-LD contexts are not supported in ontologies".to_string(),
+                ))
+            }
+        }
+        Ok(context)
+    }
+
+    /// Expand a term, compact IRI or relative IRI. Vocabulary-relative values (keys, types) use
+    /// the term definitions and `@vocab`, others the `@base`
+    fn expand(&self, value: &str, is_vocab: bool) -> String {
+        if value.starts_with('@') {
+            return value.to_string();
+        }
+        if is_vocab {
+            if let Some(definition) = self.terms.get(value) {
+                return definition.id.clone();
+            }
+        }
+        if let Some((prefix, suffix)) = value.split_once(':') {
+            if prefix == "_" || suffix.starts_with("//") {
+                return value.to_string();
+            }
+            return match self.terms.get(prefix) {
+                Some(definition) => format!("{}{}", definition.id, suffix),
+                None => value.to_string(),
+            };
+        }
+        match (&self.vocab, &self.base) {
+            (Some(vocab), _) if is_vocab => format!("{}{}", vocab, value),
+            (_, Some(base)) => url::Url::parse(base)
+                .and_then(|base| base.join(value))
+                .map(String::from)
+                .unwrap_or_else(|_| value.to_string()),
+            _ => value.to_string(),
+        }
+    }
+}
+
+struct JsonLdReader {
+    graph: Graph,
+}
+
+impl JsonLdReader {
+    fn document(&mut self, document: &JsonValue, context: &Context) -> Result<(), ProcessorError> {
+        match document {
+            JsonValue::Array(nodes) => {
+                for node in nodes {
+                    self.document(node, context)?;
+                }
+            }
+            JsonValue::Object(object) => {
+                let context = match object.get("@context") {
+                    Some(local) => context.with(local)?,
+                    None => context.clone(),
+                };
+                if let Some(insert) = object.get("insert") {
+                    return self.document(insert, &context);
+                }
+                if let Some(graph) = object.get("@graph") {
+                    self.document(graph, &context)?;
+                }
+                if object
+                    .keys()
+                    .any(|key| key != "@context" && key != "@graph")
+                {
+                    self.node(object, &context)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Add a node's triples, returning the node
+    fn node(
+        &mut self,
+        object: &Map<String, JsonValue>,
+        context: &Context,
+    ) -> Result<Term, ProcessorError> {
+        let context = match object.get("@context") {
+            Some(local) => context.with(local)?,
+            None => context.clone(),
+        };
+        let subject = match object.get("@id").and_then(JsonValue::as_str) {
+            Some(id) if id.starts_with("_:") => Term::Blank(format!("j-{}", &id[2..])),
+            Some(id) => Term::Iri(context.expand(id, false)),
+            None => self.graph.new_blank_node(),
+        };
+
+        for (key, value) in object {
+            if matches!(key.as_str(), "@reverse" | "@included" | "@nest") {
+                return Err(unsupported(key));
+            }
+            if key == "@type" {
+                for type_ in values(value) {
+                    if let Some(type_) = type_.as_str() {
+                        let type_ = Term::Iri(context.expand(type_, true));
+                        self.graph
+                            .add(subject.clone(), format!("{}type", RDF), type_);
+                    }
+                }
+                continue;
+            }
+            if key.starts_with('@') {
+                continue;
+            }
+            let predicate = context.expand(key, true);
+            // Keys that do not expand to an IRI are not part of the graph
+            if !predicate.contains(':') {
+                continue;
+            }
+            let definition = context.terms.get(key).cloned().unwrap_or_default();
+            if definition.is_list {
+                let items = values(value)
+                    .filter_map(|item| self.value(item, &definition, &context).transpose())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let list = self.graph.add_list(items);
+                self.graph.add(subject.clone(), predicate, list);
+                continue;
+            }
+            for item in values(value) {
+                if let Some(object) = self.value(item, &definition, &context)? {
+                    self.graph.add(subject.clone(), predicate.clone(), object);
+                }
+            }
+        }
+        Ok(subject)
+    }
+
+    fn value(
+        &mut self,
+        value: &JsonValue,
+        definition: &TermDefinition,
+        context: &Context,
+    ) -> Result<Option<Term>, ProcessorError> {
+        let literal = |value: String, datatype: Option<String>| Term::Literal {
+            value,
+            language: None,
+            datatype,
+        };
+        Ok(Some(match value {
+            JsonValue::String(value) if definition.is_reference => {
+                Term::Iri(context.expand(value, false))
+            }
+            JsonValue::String(value) => literal(value.clone(), definition.datatype.clone()),
+            JsonValue::Number(number) => {
+                let datatype = if number.is_f64() { "double" } else { "integer" };
+                literal(number.to_string(), Some(format!("{}{}", XSD, datatype)))
+            }
+            JsonValue::Bool(value) => literal(value.to_string(), Some(format!("{}boolean", XSD))),
+            JsonValue::Object(object) if object.contains_key("@value") => {
+                let value = match &object["@value"] {
+                    JsonValue::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                Term::Literal {
+                    value,
+                    language: object
+                        .get("@language")
+                        .and_then(JsonValue::as_str)
+                        .map(str::to_string),
+                    datatype: object
+                        .get("@type")
+                        .and_then(JsonValue::as_str)
+                        .map(|datatype| context.expand(datatype, true)),
+                }
+            }
+            JsonValue::Object(object) if object.contains_key("@list") => {
+                let items = values(&object["@list"])
+                    .filter_map(|item| self.value(item, definition, context).transpose())
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.graph.add_list(items)
+            }
+            JsonValue::Object(object) => self.node(object, context)?,
+            _ => return Ok(None),
+        }))
+    }
+}
+
+fn unsupported(keyword: &str) -> ProcessorError {
+    ProcessorError::Processing(format!(
+        "JSON-LD {} is not supported in ontologies",
+        keyword
+    ))
+}
+
+/// The values of a key, which may be a single value or an array
+fn values(value: &JsonValue) -> impl Iterator<Item = &JsonValue> {
+    match value {
+        JsonValue::Array(values) => values.iter(),
+        value => std::slice::from_ref(value).iter(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use crate::types::{IdOpt, PropertyDatatype};
+    use crate::vocabulary::ontology::{ontology_vocabulary, ImportedOntology};
+    use serde_json::json;
+
+    const MODEL: &str = "\
+Product,Product ID,Product,A product,Product ID,Identifier,@id,,,,,Produit
+Product,Name,Product,A product,Name,Product name,String,,,1,1,Produit
+Product,Tag,Product,A product,Tag,Tags,String,,,2,5,Produit
+Product,Weight,Product,A product,Weight,Weight,Decimal,,kg,,,Produit
+Product,Color,Product,A product,Color,Color,Picklist,Color,,,,Produit
+Color,Color Name,Color,A color,Color Name,Name,@id,,,,,
+";
+
+    /// Import the model, then read its own model.jsonld back as an ontology
+    async fn round_trip(output_format: &str) -> ImportedOntology {
+        let manifest = TestManifest::new()
+            .with("@id", json!("http://example.com/products"))
+            .with_model("outputFormat", json!(output_format))
+            .with_instances("baseIRI", json!("http://example.com/data/"))
+            .instance_step(
+                "colors.csv",
+                "PicklistStep",
+                json!({ "instanceType": "Color" }),
+            );
+        let model = model_csv(
+            &["Unit", "Min Count", "Max Count", "Class Name (fr)"],
+            MODEL,
+        );
+        let run = run_import(
+            &manifest.build(),
+            &[
+                ("model.csv", &model),
+                ("colors.csv", "Color Name\nRed\nBlue\n"),
+            ],
+            true,
+        )
+        .await;
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        let graph = parse(&run.model.to_string()).unwrap();
+        ontology_vocabulary(&graph, "http://example.com/terms/")
+    }
+
+    fn assert_round_trips(imported: &ImportedOntology) {
+        let id = |name: &str| IdOpt::String(name.to_string());
+        let product = &imported.vocabulary.classes[&id("Product")];
+        assert_eq!(product.label.as_deref(), Some("Product"));
+        assert_eq!(product.localized_labels["fr"], "Produit");
+        let mut properties = imported.class_properties[&id("Product")].clone();
+        properties.sort();
+        assert_eq!(
+            properties,
+            ["color", "name", "tag", "weight"]
+                .map(|name| format!("http://example.com/terms/{}", name))
+        );
+
+        let color = &imported.vocabulary.classes[&id("Color")];
+        assert_eq!(
+            color.one_of,
+            Some(vec![
+                id("http://example.com/data/Red"),
+                id("http://example.com/data/Blue")
+            ])
+        );
+
+        let properties = &imported.vocabulary.properties;
+        assert_eq!(
+            properties[&id("color")].range,
+            Some(vec![PropertyDatatype::Picklist(Some(
+                "http://example.com/terms/Color".to_string()
+            ))])
+        );
+        assert_eq!(
+            properties[&id("name")].range,
+            Some(vec![PropertyDatatype::String])
+        );
+        let weight = &properties[&id("weight")];
+        assert_eq!(weight.range, Some(vec![PropertyDatatype::Decimal]));
+        assert_eq!(
+            weight.unit.as_deref(),
+            Some("http://qudt.org/vocab/unit/KiloGM")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rdfs_model_round_trip() {
+        let imported = round_trip("RDFS").await;
+        assert_round_trips(&imported);
+    }
+
+    #[tokio::test]
+    async fn test_owl_model_round_trip() {
+        let imported = round_trip("OWL").await;
+        assert_round_trips(&imported);
+
+        // Only the OWL output carries counts, as restrictions
+        let properties = &imported.vocabulary.properties;
+        let counts = |name: &str| {
+            let property = &properties[&IdOpt::String(name.to_string())];
+            (property.min_count, property.max_count)
+        };
+        assert_eq!(counts("name"), (Some(1), Some(1)));
+        assert_eq!(counts("tag"), (Some(2), Some(5)));
+    }
+
+    #[test]
+    fn test_unsupported_constructs_are_rejected() {
+        let error = |document: JsonValue| parse(&document.to_string()).unwrap_err().to_string();
+        assert!(
+            error(json!({ "@context": "https://schema.org/", "@id": "ex:a" }))
+                .contains("Remote JSON-LD contexts are not supported")
+        );
+        for keyword in ["@reverse", "@included", "@nest"] {
+            let document = json!({ "@id": "http://example.com/a", keyword: {} });
+            assert!(
+                error(document).contains(&format!("JSON-LD {} is not supported", keyword)),
+                "{keyword}"
+            );
+        }
+        let document = json!({
+            "@context": { "label": { "@id": "http://example.com/label", "@container": "@language" } },
+            "@id": "http://example.com/a"
+        });
+        assert!(error(document).contains("JSON-LD @language is not supported"));
+        let document = json!({
+            "@context": { "partOf": { "@reverse": "http://example.com/has" } },
+            "@id": "http://example.com/a"
+        });
+        assert!(error(document).contains("JSON-LD @reverse is not supported"));
+    }
+}
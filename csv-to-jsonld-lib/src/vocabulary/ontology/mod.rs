@@ -0,0 +1,455 @@
+mod json_ld;
+mod turtle;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::error::ProcessorError;
+use crate::types::{IdOpt, PropertyDatatype, ValueConstraints, VocabularyMap, VocabularyTerm};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const RDFS: &str = "http://www.w3.org/2000/01/rdf-schema#";
+const OWL: &str = "http://www.w3.org/2002/07/owl#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const QUDT: &str = "http://qudt.org/schema/qudt/";
+const FLUREE: &str = "https://ns.flur.ee/ledger#";
+
+/// An RDF node or literal read from an ontology
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Term {
+    Iri(String),
+    Blank(String),
+    Literal {
+        value: String,
+        language: Option<String>,
+        datatype: Option<String>,
+    },
+}
+
+impl Term {
+    fn iri(&self) -> Option<&str> {
+        match self {
+            Term::Iri(iri) => Some(iri),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Triple {
+    pub subject: Term,
+    pub predicate: String,
+    pub object: Term,
+}
+
+/// The triples of an ontology document
+#[derive(Debug, Default)]
+pub(crate) struct Graph {
+    triples: Vec<Triple>,
+    /// Indices into `triples` by subject, so looking up a node's objects doesn't scan the graph
+    by_subject: HashMap<Term, Vec<usize>>,
+    blank_nodes: usize,
+}
+
+impl Graph {
+    fn add(&mut self, subject: Term, predicate: impl Into<String>, object: Term) {
+        self.by_subject
+            .entry(subject.clone())
+            .or_default()
+            .push(self.triples.len());
+        self.triples.push(Triple {
+            subject,
+            predicate: predicate.into(),
+            object,
+        });
+    }
+
+    fn new_blank_node(&mut self) -> Term {
+        self.blank_nodes += 1;
+        Term::Blank(format!("b{}", self.blank_nodes))
+    }
+
+    /// Link items as an RDF list, returning its head
+    fn add_list(&mut self, items: Vec<Term>) -> Term {
+        let mut rest = Term::Iri(format!("{}nil", RDF));
+        for item in items.into_iter().rev() {
+            let node = self.new_blank_node();
+            self.add(node.clone(), format!("{}first", RDF), item);
+            self.add(node.clone(), format!("{}rest", RDF), rest);
+            rest = node;
+        }
+        rest
+    }
+
+    fn objects<'a>(
+        &'a self,
+        subject: &'a Term,
+        predicate: &'a str,
+    ) -> impl Iterator<Item = &'a Term> {
+        self.by_subject
+            .get(subject)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.triples[i])
+            .filter(move |t| t.predicate == predicate)
+            .map(|t| &t.object)
+    }
+
+    fn object_iris<'a>(&'a self, subject: &'a Term, predicate: &'a str) -> Vec<String> {
+        self.objects(subject, predicate)
+            .filter_map(Term::iri)
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn has_type(&self, subject: &Term, types: &[String]) -> bool {
+        self.objects(subject, &format!("{}type", RDF))
+            .any(|t| t.iri().is_some_and(|iri| types.iter().any(|ty| ty == iri)))
+    }
+
+    fn subjects_of_type(&self, types: &[String]) -> Vec<&str> {
+        let rdf_type = format!("{}type", RDF);
+        let mut subjects: Vec<&str> = self
+            .triples
+            .iter()
+            .filter(|t| t.predicate == rdf_type)
+            .filter(|t| {
+                t.object
+                    .iri()
+                    .is_some_and(|iri| types.iter().any(|ty| ty == iri))
+            })
+            .filter_map(|t| t.subject.iri())
+            .collect();
+        subjects.sort();
+        subjects.dedup();
+        subjects
+    }
+
+    /// The items of the RDF list starting at a node
+    fn list(&self, head: &Term) -> Vec<Term> {
+        let (first, rest) = (format!("{}first", RDF), format!("{}rest", RDF));
+        let mut items = vec![];
+        let mut node = head.clone();
+        let mut seen = HashSet::new();
+        while matches!(node, Term::Blank(_)) && seen.insert(node.clone()) {
+            items.extend(self.objects(&node, &first).next().cloned());
+            let next = self.objects(&node, &rest).next().cloned();
+            match next {
+                Some(next) => node = next,
+                None => break,
+            }
+        }
+        items
+    }
+}
+
+/// Ontology document syntaxes that can be imported
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum OntologyFormat {
+    Turtle,
+    JsonLd,
+}
+
+impl OntologyFormat {
+    /// Turtle for ".ttl" files, JSON-LD otherwise
+    pub(crate) fn from_path(path: &str) -> Self {
+        if path.to_lowercase().ends_with(".ttl") {
+            OntologyFormat::Turtle
+        } else {
+            OntologyFormat::JsonLd
+        }
+    }
+}
+
+pub(crate) fn parse_ontology(
+    contents: &str,
+    format: OntologyFormat,
+) -> Result<Graph, ProcessorError> {
+    match format {
+        OntologyFormat::Turtle => turtle::parse(contents),
+        OntologyFormat::JsonLd => json_ld::parse(contents),
+    }
+}
+
+/// Classes and properties read from an ontology, keyed like model CSV terms: by their name
+/// relative to the model base IRI, or by full IRI outside it
+#[derive(Debug, Default)]
+pub(crate) struct ImportedOntology {
+    pub vocabulary: VocabularyMap,
+    pub class_properties: HashMap<IdOpt, Vec<String>>,
+}
+
+/// Read the RDFS and OWL classes and properties of an ontology. Restrictions give property counts,
+/// `owl:oneOf` enumerations (or `f:oneOf`) make picklist classes
+pub(crate) fn ontology_vocabulary(graph: &Graph, base_iri: &str) -> ImportedOntology {
+    let key = |iri: &str| {
+        IdOpt::String(
+            iri.strip_prefix(base_iri)
+                .filter(|name| !base_iri.is_empty() && !name.is_empty())
+                .unwrap_or(iri)
+                .to_string(),
+        )
+    };
+    let class_types = [format!("{}Class", RDFS), format!("{}Class", OWL)];
+    let object_property_types = [format!("{}ObjectProperty", OWL)];
+    let property_types = [
+        format!("{}Property", RDF),
+        format!("{}ObjectProperty", OWL),
+        format!("{}DatatypeProperty", OWL),
+    ];
+
+    let mut imported = ImportedOntology::default();
+
+    let picklist_values: HashMap<&str, Vec<IdOpt>> = graph
+        .subjects_of_type(&class_types)
+        .into_iter()
+        .filter_map(|class| {
+            let node = Term::Iri(class.to_string());
+            let mut values: Vec<Term> = graph
+                .objects(&node, &format!("{}oneOf", OWL))
+                .flat_map(|head| graph.list(head))
+                .collect();
+            values.extend(graph.objects(&node, &format!("{}oneOf", FLUREE)).cloned());
+            let values: Vec<IdOpt> = values
+                .iter()
+                .filter_map(Term::iri)
+                .map(|iri| IdOpt::String(iri.to_string()))
+                .collect();
+            (!values.is_empty()).then_some((class, values))
+        })
+        .collect();
+
+    for property in graph.subjects_of_type(&property_types) {
+        let node = Term::Iri(property.to_string());
+        let unit = graph.object_iris(&node, &format!("{}unit", QUDT)).pop();
+        let is_object_property = graph.has_type(&node, &object_property_types);
+        let ranges = graph.object_iris(&node, &format!("{}range", RDFS));
+        let range = match ranges.first() {
+            Some(range) if unit.is_some() && range == &format!("{}QuantityValue", QUDT) => {
+                PropertyDatatype::Decimal
+            }
+            Some(range) => range_datatype(range, picklist_values.contains_key(range.as_str())),
+            None if is_object_property => PropertyDatatype::URI(None),
+            None => PropertyDatatype::String,
+        };
+        let (label, localized_labels) = texts(graph, &node, "label");
+        let (comment, localized_comments) = texts(graph, &node, "comment");
+        let domain = graph.object_iris(&node, &format!("{}domain", RDFS));
+        let term = VocabularyTerm {
+            id: IdOpt::String(property.to_string()),
+            type_: vec!["rdf:Property".to_string()],
+            label: label.or_else(|| Some(local_name(property).to_string())),
+            sub_class_of: None,
+            comment,
+            localized_labels,
+            localized_comments,
+            domain: (!domain.is_empty()).then_some(domain),
+            range: Some(vec![range]),
+            unit,
+            min_count: None,
+            max_count: None,
+            constraints: ValueConstraints::default(),
+            extra_items: HashMap::new(),
+            one_of: None,
+        };
+        imported.vocabulary.properties.insert(key(property), term);
+    }
+
+    for class in graph.subjects_of_type(&class_types) {
+        let node = Term::Iri(class.to_string());
+        let (label, localized_labels) = texts(graph, &node, "label");
+        let (comment, localized_comments) = texts(graph, &node, "comment");
+        let mut sub_class_of = vec![];
+        // The model's own RDFS output lists a class's properties as its range
+        let mut properties = graph.object_iris(&node, &format!("{}range", RDFS));
+        for parent in graph.objects(&node, &format!("{}subClassOf", RDFS)) {
+            match parent {
+                Term::Iri(parent) => sub_class_of.push(parent.clone()),
+                Term::Blank(_) => {
+                    if let Some(property) = apply_restriction(graph, parent, &mut imported) {
+                        properties.push(property);
+                    }
+                }
+                Term::Literal { .. } => {}
+            }
+        }
+        properties.extend(
+            imported
+                .vocabulary
+                .properties
+                .values()
+                .filter(|property| property.domain.iter().flatten().any(|d| d == class))
+                .map(|property| property.id.final_iri()),
+        );
+        let mut seen = HashSet::new();
+        properties.retain(|property| {
+            seen.insert(property.clone())
+                && imported
+                    .vocabulary
+                    .properties
+                    .values()
+                    .any(|p| &p.id.final_iri() == property)
+        });
+
+        let term = VocabularyTerm {
+            id: IdOpt::String(class.to_string()),
+            type_: vec!["rdfs:Class".to_string()],
+            label: label.or_else(|| Some(local_name(class).to_string())),
+            sub_class_of: (!sub_class_of.is_empty()).then_some(sub_class_of),
+            comment,
+            localized_labels,
+            localized_comments,
+            domain: None,
+            range: Some(
+                properties
+                    .iter()
+                    .map(|property| PropertyDatatype::URI(Some(property.clone())))
+                    .collect(),
+            ),
+            unit: None,
+            min_count: None,
+            max_count: None,
+            constraints: ValueConstraints::default(),
+            extra_items: HashMap::new(),
+            one_of: picklist_values.get(class).cloned(),
+        };
+        imported.vocabulary.classes.insert(key(class), term);
+        imported.class_properties.insert(key(class), properties);
+    }
+
+    imported
+}
+
+/// The datatype of a property with the given range IRI
+fn range_datatype(range: &str, is_picklist: bool) -> PropertyDatatype {
+    if let Some(xsd_type) = range.strip_prefix(XSD) {
+        return match xsd_type {
+            "int" | "short" | "byte" | "nonNegativeInteger" | "positiveInteger"
+            | "negativeInteger" | "nonPositiveInteger" | "unsignedInt" | "unsignedLong" => {
+                PropertyDatatype::Integer
+            }
+            "double" => PropertyDatatype::Decimal,
+            xsd_type => xsd_type.parse().unwrap_or(PropertyDatatype::String),
+        };
+    }
+    if range == format!("{}langString", RDF) {
+        PropertyDatatype::LangString
+    } else if range == format!("{}Literal", RDFS) {
+        PropertyDatatype::String
+    } else if is_picklist {
+        PropertyDatatype::Picklist(Some(range.to_string()))
+    } else {
+        PropertyDatatype::URI(Some(range.to_string()))
+    }
+}
+
+/// Apply an `owl:Restriction`'s cardinality to its property, returning the property
+fn apply_restriction(
+    graph: &Graph,
+    restriction: &Term,
+    imported: &mut ImportedOntology,
+) -> Option<String> {
+    let property = graph
+        .object_iris(restriction, &format!("{}onProperty", OWL))
+        .pop()?;
+    let count = |names: &[&str]| {
+        names.iter().find_map(|name| {
+            graph
+                .objects(restriction, &format!("{}{}", OWL, name))
+                .find_map(|count| match count {
+                    Term::Literal { value, .. } => value.parse::<usize>().ok(),
+                    _ => None,
+                })
+        })
+    };
+    let exact = count(&["cardinality", "qualifiedCardinality"]);
+    let min_count = exact.or(count(&["minCardinality", "minQualifiedCardinality"]));
+    let max_count = exact.or(count(&["maxCardinality", "maxQualifiedCardinality"]));
+    let term = imported
+        .vocabulary
+        .properties
+        .values_mut()
+        .find(|term| term.id.final_iri() == property)?;
+    term.min_count = min_count.or(term.min_count);
+    term.max_count = max_count.or(term.max_count);
+    Some(property)
+}
+
+/// The untagged text and the language-tagged texts of an `rdfs:label` or `rdfs:comment`. Without
+/// an untagged text, the English (or first) tagged one is used
+fn texts(graph: &Graph, node: &Term, name: &str) -> (Option<String>, BTreeMap<String, String>) {
+    let mut text = None;
+    let mut localized = BTreeMap::new();
+    for object in graph.objects(node, &format!("{}{}", RDFS, name)) {
+        if let Term::Literal {
+            value, language, ..
+        } = object
+        {
+            match language {
+                Some(language) => {
+                    localized.insert(language.clone(), value.clone());
+                }
+                None => text = Some(value.clone()),
+            }
+        }
+    }
+    let text = text.or_else(|| {
+        localized
+            .get("en")
+            .or_else(|| localized.values().next())
+            .cloned()
+    });
+    (text, localized)
+}
+
+/// The part of an IRI after its last '#' or '/'
+fn local_name(iri: &str) -> &str {
+    iri.rsplit(['#', '/']).next().unwrap_or(iri)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turtle_ontology_vocabulary() {
+        let contents = r#"
+            @prefix ex: <http://example.com/terms/> .
+            @prefix owl: <http://www.w3.org/2002/07/owl#> .
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+            @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+            ex:Part a owl:Class ;
+                rdfs:label "Part", "Teil"@de ;
+                rdfs:subClassOf [ a owl:Restriction ; owl:onProperty ex:color ; owl:cardinality 1 ] .
+            ex:Color a owl:Class ; owl:oneOf ( ex:Red ex:Blue ) .
+            ex:name a owl:DatatypeProperty ; rdfs:domain ex:Part ; rdfs:range xsd:string .
+            ex:color a owl:ObjectProperty ; rdfs:label "Color" ; rdfs:range ex:Color .
+        "#;
+        let graph = parse_ontology(contents, OntologyFormat::Turtle).unwrap();
+        let imported = ontology_vocabulary(&graph, "http://example.com/terms/");
+
+        let part = &imported.vocabulary.classes[&IdOpt::String("Part".to_string())];
+        assert_eq!(part.label.as_deref(), Some("Part"));
+        assert_eq!(part.localized_labels["de"], "Teil");
+        assert_eq!(
+            imported.class_properties[&IdOpt::String("Part".to_string())],
+            vec![
+                "http://example.com/terms/color".to_string(),
+                "http://example.com/terms/name".to_string()
+            ]
+        );
+
+        let color = &imported.vocabulary.properties[&IdOpt::String("color".to_string())];
+        assert_eq!(
+            color.range,
+            Some(vec![PropertyDatatype::Picklist(Some(
+                "http://example.com/terms/Color".to_string()
+            ))])
+        );
+        assert_eq!((color.min_count, color.max_count), (Some(1), Some(1)));
+
+        let name = &imported.vocabulary.properties[&IdOpt::String("name".to_string())];
+        assert_eq!(name.label.as_deref(), Some("name"));
+        assert_eq!(name.range, Some(vec![PropertyDatatype::String]));
+    }
+}
@@ -0,0 +1,590 @@
+use std::collections::HashMap;
+
+use super::{Graph, Term, RDF, XSD};
+use crate::error::ProcessorError;
+
+/// Parse a Turtle document into triples. Supports Turtle 1.1 except escapes inside IRI references;
+/// RDF-star quoted triples and TriG or N3 blocks are rejected
+pub(super) fn parse(contents: &str) -> Result<Graph, ProcessorError> {
+    let mut parser = TurtleParser {
+        chars: contents.chars().collect(),
+        pos: 0,
+        prefixes: HashMap::new(),
+        base: None,
+        graph: Graph::default(),
+    };
+    parser.document()?;
+    Ok(parser.graph)
+}
+
+struct TurtleParser {
+    chars: Vec<char>,
+    pos: usize,
+    prefixes: HashMap<String, String>,
+    base: Option<url::Url>,
+    graph: Graph,
+}
+
+impl TurtleParser {
+    fn document(&mut self) -> Result<(), ProcessorError> {
+        loop {
+            self.skip_whitespace();
+            if self.pos >= self.chars.len() {
+                return Ok(());
+            }
+            if self.peek() == Some('@') {
+                self.pos += 1;
+                let directive = self.name();
+                self.directive(&directive)?;
+                self.expect('.')?;
+            } else if self.keyword("PREFIX") || self.keyword("BASE") {
+                let directive = self.name().to_lowercase();
+                self.directive(&directive)?;
+            } else {
+                self.triples()?;
+                self.expect('.')?;
+            }
+        }
+    }
+
+    fn directive(&mut self, directive: &str) -> Result<(), ProcessorError> {
+        self.skip_whitespace();
+        match directive {
+            "prefix" => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != ':' && !c.is_whitespace()) {
+                    self.pos += 1;
+                }
+                let prefix = self.chars[start..self.pos].iter().collect();
+                self.expect(':')?;
+                self.skip_whitespace();
+                let iri = self.iri_ref()?;
+                self.prefixes.insert(prefix, iri);
+            }
+            "base" => {
+                let iri = self.iri_ref()?;
+                self.base = url::Url::parse(&iri).ok();
+            }
+            _ => return Err(self.error(&format!("unknown directive '{}'", directive))),
+        }
+        Ok(())
+    }
+
+    fn triples(&mut self) -> Result<(), ProcessorError> {
+        let subject = match self.peek() {
+            Some('[') => {
+                self.pos += 1;
+                let subject = self.graph.new_blank_node();
+                self.skip_whitespace();
+                if self.peek() == Some(']') {
+                    self.pos += 1;
+                } else {
+                    self.predicate_objects(&subject)?;
+                    self.expect(']')?;
+                }
+                self.skip_whitespace();
+                // A blank node subject may stand alone
+                if self.peek() == Some('.') {
+                    return Ok(());
+                }
+                subject
+            }
+            Some('(') => self.collection()?,
+            _ => self.resource()?,
+        };
+        self.predicate_objects(&subject)
+    }
+
+    fn predicate_objects(&mut self, subject: &Term) -> Result<(), ProcessorError> {
+        loop {
+            self.skip_whitespace();
+            // `a` is only the keyword when it is not the start of a longer name
+            let predicate = if self.peek() == Some('a')
+                && self
+                    .chars
+                    .get(self.pos + 1)
+                    .is_none_or(|c| !is_name_char(*c))
+            {
+                self.pos += 1;
+                format!("{}type", RDF)
+            } else {
+                match self.resource()? {
+                    Term::Iri(iri) => iri,
+                    _ => return Err(self.error("expected a predicate IRI")),
+                }
+            };
+            loop {
+                self.skip_whitespace();
+                let object = self.object()?;
+                self.graph.add(subject.clone(), predicate.clone(), object);
+                self.skip_whitespace();
+                if self.peek() == Some(',') {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+            // Repeated semicolons are allowed
+            let mut has_more = false;
+            while self.peek() == Some(';') {
+                self.pos += 1;
+                has_more = true;
+                self.skip_whitespace();
+            }
+            if !has_more || matches!(self.peek(), Some('.' | ']') | None) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Term, ProcessorError> {
+        match self.peek() {
+            Some('[') => {
+                self.pos += 1;
+                let node = self.graph.new_blank_node();
+                self.skip_whitespace();
+                if self.peek() != Some(']') {
+                    self.predicate_objects(&node)?;
+                }
+                self.expect(']')?;
+                Ok(node)
+            }
+            Some('(') => self.collection(),
+            Some('"' | '\'') => self.literal(),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => self.number(),
+            _ if self.keyword("true") || self.keyword("false") => Ok(Term::Literal {
+                value: self.name(),
+                language: None,
+                datatype: Some(format!("{}boolean", XSD)),
+            }),
+            _ => self.resource(),
+        }
+    }
+
+    fn collection(&mut self) -> Result<Term, ProcessorError> {
+        self.expect('(')?;
+        let mut items = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(')') {
+                self.pos += 1;
+                return Ok(self.graph.add_list(items));
+            }
+            items.push(self.object()?);
+        }
+    }
+
+    /// An IRI, prefixed name or blank node label
+    fn resource(&mut self) -> Result<Term, ProcessorError> {
+        self.skip_whitespace();
+        if self.chars.get(self.pos..self.pos + 2) == Some(&['<', '<']) {
+            return Err(self.error("RDF-star quoted triples are not supported"));
+        }
+        if self.peek() == Some('<') {
+            return self.iri_ref().map(Term::Iri);
+        }
+        let name = self.name();
+        if name.is_empty() {
+            return Err(match self.peek() {
+                Some('{') => self.error("graph blocks (TriG or N3) are not supported"),
+                Some(c) => self.error(&format!("unexpected '{}'", c)),
+                None => self.error("unexpected end of document"),
+            });
+        }
+        let Some((prefix, local)) = name.split_once(':') else {
+            return Err(self.error(&format!("expected an IRI but found '{}'", name)));
+        };
+        if prefix == "_" {
+            return Ok(Term::Blank(format!("t-{}", local)));
+        }
+        match self.prefixes.get(prefix) {
+            Some(namespace) => Ok(Term::Iri(format!("{}{}", namespace, unescape_local(local)))),
+            None => Err(self.error(&format!("undefined prefix '{}'", prefix))),
+        }
+    }
+
+    fn iri_ref(&mut self) -> Result<String, ProcessorError> {
+        self.expect('<')?;
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c != '>') {
+            self.pos += 1;
+        }
+        let iri: String = self.chars[start..self.pos].iter().collect();
+        self.expect('>')?;
+        Ok(match &self.base {
+            Some(base) => base.join(&iri).map(String::from).unwrap_or(iri),
+            None => iri,
+        })
+    }
+
+    fn literal(&mut self) -> Result<Term, ProcessorError> {
+        let quote = self.peek().unwrap_or('"');
+        let is_long = self.chars.get(self.pos..self.pos + 3) == Some(&[quote; 3]);
+        self.pos += if is_long { 3 } else { 1 };
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            // A long string closes at the last three of a run of quotes, so it may end with a quote
+            if is_long
+                && self.chars.get(self.pos..self.pos + 3) == Some(&[quote; 3])
+                && self.chars.get(self.pos + 3) != Some(&quote)
+            {
+                self.pos += 3;
+                break;
+            }
+            self.pos += 1;
+            if !is_long && c == quote {
+                break;
+            }
+            if c == '\\' {
+                value.push(self.escape()?);
+            } else {
+                value.push(c);
+            }
+        }
+
+        let mut language = None;
+        let mut datatype = None;
+        if self.peek() == Some('@') {
+            self.pos += 1;
+            language = Some(self.name());
+        } else if self.chars.get(self.pos..self.pos + 2) == Some(&['^', '^']) {
+            self.pos += 2;
+            datatype = self.resource()?.iri().map(str::to_string);
+        }
+        Ok(Term::Literal {
+            value,
+            language,
+            datatype,
+        })
+    }
+
+    fn escape(&mut self) -> Result<char, ProcessorError> {
+        let c = self
+            .peek()
+            .ok_or_else(|| self.error("unterminated escape"))?;
+        self.pos += 1;
+        let hex_digits = match c {
+            't' => return Ok('\t'),
+            'n' => return Ok('\n'),
+            'r' => return Ok('\r'),
+            'b' => return Ok('\u{8}'),
+            'f' => return Ok('\u{c}'),
+            'u' => 4,
+            'U' => 8,
+            c => return Ok(c),
+        };
+        let hex: String = self
+            .chars
+            .get(self.pos..self.pos + hex_digits)
+            .unwrap_or_default()
+            .iter()
+            .collect();
+        self.pos += hex_digits;
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(&format!("invalid escape '\\{}{}'", c, hex)))
+    }
+
+    fn number(&mut self) -> Result<Term, ProcessorError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            self.pos += 1;
+        }
+        // A trailing '.' ends the statement
+        if self.chars[self.pos - 1] == '.' {
+            self.pos -= 1;
+        }
+        let value: String = self.chars[start..self.pos].iter().collect();
+        let datatype = if value.contains(['e', 'E']) {
+            "double"
+        } else if value.contains('.') {
+            "decimal"
+        } else {
+            "integer"
+        };
+        Ok(Term::Literal {
+            value,
+            language: None,
+            datatype: Some(format!("{}{}", XSD, datatype)),
+        })
+    }
+
+    /// A run of name characters, such as a prefixed name, language tag or directive
+    fn name(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_name_char) {
+            if self.peek() == Some('\\') {
+                self.pos += 1;
+            }
+            self.pos += 1;
+        }
+        // Names cannot end with '.', which ends the statement instead
+        while self.pos > start && self.chars[self.pos - 1] == '.' {
+            self.pos -= 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn keyword(&self, keyword: &str) -> bool {
+        let end = self.pos + keyword.len();
+        self.chars.get(self.pos..end).is_some_and(|chars| {
+            chars
+                .iter()
+                .collect::<String>()
+                .eq_ignore_ascii_case(keyword)
+        }) && self
+            .chars
+            .get(end)
+            .is_none_or(|c| !c.is_alphanumeric() && *c != ':')
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '#' {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else if c.is_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ProcessorError> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", expected)))
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn error(&self, message: &str) -> ProcessorError {
+        let line = self.chars[..self.pos.min(self.chars.len())]
+            .iter()
+            .filter(|c| **c == '\n')
+            .count()
+            + 1;
+        ProcessorError::Processing(format!(
+            "Failed to parse Turtle ontology at line {}: {}",
+            line, message
+        ))
+    }
+}
+
+/// A character of a prefixed name, language tag or directive
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || "_-:.%\\".contains(c)
+}
+
+/// Drop the backslashes of escaped characters in a prefixed name's local part
+fn unescape_local(local: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = local.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            unescaped.extend(chars.next());
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(value: &str, datatype: &str) -> Term {
+        Term::Literal {
+            value: value.to_string(),
+            language: None,
+            datatype: Some(format!("{}{}", XSD, datatype)),
+        }
+    }
+
+    fn iri(iri: &str) -> Term {
+        Term::Iri(iri.to_string())
+    }
+
+    fn objects(graph: &Graph, subject: &str, predicate: &str) -> Vec<Term> {
+        graph.objects(&iri(subject), predicate).cloned().collect()
+    }
+
+    #[test]
+    fn test_sparql_prefix_and_base() {
+        let graph = parse(
+            r#"
+            BASE <http://example.com/terms/>
+            prefix ex: <http://example.com/other/>
+            <Part> ex:p <color> .
+            @base <http://example.com/data/> .
+            <Red> ex:p ex:q .
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            objects(
+                &graph,
+                "http://example.com/terms/Part",
+                "http://example.com/other/p"
+            ),
+            vec![iri("http://example.com/terms/color")]
+        );
+        assert_eq!(
+            objects(
+                &graph,
+                "http://example.com/data/Red",
+                "http://example.com/other/p"
+            ),
+            vec![iri("http://example.com/other/q")]
+        );
+    }
+
+    #[test]
+    fn test_collections() {
+        let graph = parse(
+            r#"
+            @prefix ex: <http://example.com/> .
+            ex:a ex:list ( ex:b ( 1 "two" ) ) ; ex:empty () .
+        "#,
+        )
+        .unwrap();
+
+        let list = objects(&graph, "http://example.com/a", "http://example.com/list");
+        let items = graph.list(&list[0]);
+        assert_eq!(items[0], iri("http://example.com/b"));
+        assert_eq!(
+            graph.list(&items[1]),
+            vec![
+                literal("1", "integer"),
+                Term::Literal {
+                    value: "two".to_string(),
+                    language: None,
+                    datatype: None,
+                }
+            ]
+        );
+        assert_eq!(
+            objects(&graph, "http://example.com/a", "http://example.com/empty"),
+            vec![iri(&format!("{}nil", RDF))]
+        );
+    }
+
+    #[test]
+    fn test_long_strings() {
+        let graph = parse(
+            r#"
+            @prefix ex: <http://example.com/> .
+            ex:a ex:p """A "quoted"
+word""" , '''It's ''fine''' , """ends with a quote"""" , """é"""@fr .
+        "#,
+        )
+        .unwrap();
+
+        let values: Vec<String> = objects(&graph, "http://example.com/a", "http://example.com/p")
+            .into_iter()
+            .map(|term| match term {
+                Term::Literal { value, .. } => value,
+                term => panic!("expected a literal but found {:?}", term),
+            })
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                "A \"quoted\"\nword",
+                "It's ''fine",
+                "ends with a quote\"",
+                "é"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_numbers_ending_statements() {
+        let graph = parse(
+            r#"
+            @prefix ex: <http://example.com/> .
+            ex:a ex:integer 1.
+            ex:a ex:decimal 1.5.
+            ex:a ex:double -2.0e3.
+            ex:a ex:leading .5 .
+        "#,
+        )
+        .unwrap();
+
+        let object = |predicate: &str| {
+            objects(
+                &graph,
+                "http://example.com/a",
+                &format!("http://example.com/{}", predicate),
+            )
+        };
+        assert_eq!(object("integer"), vec![literal("1", "integer")]);
+        assert_eq!(object("decimal"), vec![literal("1.5", "decimal")]);
+        assert_eq!(object("double"), vec![literal("-2.0e3", "double")]);
+        assert_eq!(object("leading"), vec![literal(".5", "decimal")]);
+    }
+
+    #[test]
+    fn test_undefined_prefix_reports_line() {
+        let error = parse("@prefix ex: <http://example.com/> .\nex:a ex:p nope:b .").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("line 2: undefined prefix 'nope'"));
+    }
+
+    #[test]
+    fn test_a_keyword_and_names_starting_with_a() {
+        let graph = parse(
+            r#"
+            @prefix ex: <http://example.com/> .
+            @prefix a: <http://example.com/a/> .
+            ex:s a( ex:b ) ; a:p ex:o ; ex:p a:o .
+        "#,
+        )
+        .unwrap();
+
+        let types = objects(&graph, "http://example.com/s", &format!("{}type", RDF));
+        assert_eq!(graph.list(&types[0]), vec![iri("http://example.com/b")]);
+        assert_eq!(
+            objects(&graph, "http://example.com/s", "http://example.com/a/p"),
+            vec![iri("http://example.com/o")]
+        );
+        assert_eq!(
+            objects(&graph, "http://example.com/s", "http://example.com/p"),
+            vec![iri("http://example.com/a/o")]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_syntax_is_rejected() {
+        let error = |turtle: &str| parse(turtle).unwrap_err().to_string();
+        assert!(error(
+            "<< <http://example.com/a> <http://example.com/p> 1 >> <http://example.com/q> 2 ."
+        )
+        .contains("RDF-star quoted triples are not supported"));
+        assert!(error(
+            "<http://example.com/g> { <http://example.com/a> <http://example.com/p> 1 . }"
+        )
+        .contains("graph blocks (TriG or N3) are not supported"));
+        assert!(
+            error("<http://example.com/a> <http://example.com/p> ; .").contains("unexpected ';'")
+        );
+    }
+}
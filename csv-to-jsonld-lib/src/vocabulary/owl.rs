@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::error::ProcessorError;
+use crate::types::{PropertyDatatype, VocabularyMap, VocabularyTerm};
+use crate::Manifest;
+
+/// The model as an OWL ontology: an `owl:Ontology` header from the manifest, then the classes and
+/// properties. Picklist values become `owl:oneOf` enumerations, and property counts become
+/// restrictions on the classes that have the property
+pub(crate) fn owl_ontology(
+    manifest: &Manifest,
+    vocabulary: &VocabularyMap,
+) -> Result<Vec<JsonValue>, ProcessorError> {
+    let mut ontology = vec![json!({
+        "@id": manifest.id,
+        "@type": "owl:Ontology",
+        "rdfs:label": manifest.name,
+        "rdfs:comment": manifest.description,
+    })];
+
+    let properties: HashMap<String, &VocabularyTerm> = vocabulary
+        .properties
+        .values()
+        .map(|property| (property.id.final_iri(), property))
+        .collect();
+
+    let mut classes: Vec<&VocabularyTerm> = vocabulary.classes.values().collect();
+    classes.sort_by_key(|class| class.id.final_iri());
+    for class in classes {
+        let mut term = term_map(class)?;
+        term.insert("@type".to_string(), json!("owl:Class"));
+        // A class's range lists its properties, which OWL has no use for
+        term.remove("rdfs:range");
+        if let Some(JsonValue::Array(values)) = term.remove("f:oneOf") {
+            let values: Vec<JsonValue> = values
+                .into_iter()
+                .map(|value| json!({ "@id": value }))
+                .collect();
+            term.insert("owl:oneOf".to_string(), json!({ "@list": values }));
+        }
+
+        let restrictions = class
+            .range
+            .iter()
+            .flatten()
+            .filter_map(|range| match range {
+                PropertyDatatype::URI(Some(iri)) => properties.get(iri),
+                _ => None,
+            })
+            .flat_map(|property| cardinality_restrictions(property));
+        let mut sub_class_of = match term.remove("rdfs:subClassOf") {
+            Some(JsonValue::Array(sub_class_of)) => sub_class_of,
+            _ => vec![],
+        };
+        sub_class_of.extend(restrictions);
+        if !sub_class_of.is_empty() {
+            term.insert("rdfs:subClassOf".to_string(), json!(sub_class_of));
+        }
+        ontology.push(JsonValue::Object(term));
+    }
+
+    let mut properties: Vec<&VocabularyTerm> = vocabulary.properties.values().collect();
+    properties.sort_by_key(|property| property.id.final_iri());
+    for property in properties {
+        let mut term = term_map(property)?;
+        let range = property.range.as_ref().and_then(|range| range.first());
+        let is_object_property = property.unit.is_some()
+            || matches!(
+                range,
+                Some(PropertyDatatype::URI(_) | PropertyDatatype::Picklist(_))
+            );
+        let property_type = if is_object_property {
+            "owl:ObjectProperty"
+        } else {
+            "owl:DatatypeProperty"
+        };
+        term.insert("@type".to_string(), json!(property_type));
+        // Values with a unit are quantity nodes
+        if property.unit.is_some() {
+            term.insert("rdfs:range".to_string(), json!(["qudt:QuantityValue"]));
+        }
+        ontology.push(JsonValue::Object(term));
+    }
+
+    Ok(ontology)
+}
+
+/// A term in its RDFS form, to be adjusted to OWL
+fn term_map(term: &VocabularyTerm) -> Result<Map<String, JsonValue>, ProcessorError> {
+    match serde_json::to_value(term) {
+        Ok(JsonValue::Object(map)) => Ok(map),
+        Ok(_) => Err(ProcessorError::Processing(format!(
+            "Failed to serialize term {}",
+            term.id.final_iri()
+        ))),
+        Err(e) => Err(ProcessorError::Processing(format!(
+            "Failed to serialize term {}: {}",
+            term.id.final_iri(),
+            e
+        ))),
+    }
+}
+
+/// `owl:Restriction`s for a property's min and max count, or an exact cardinality when they match
+fn cardinality_restrictions(property: &VocabularyTerm) -> Vec<JsonValue> {
+    let restriction = |key: &str, count: usize| {
+        json!({
+            "@type": "owl:Restriction",
+            "owl:onProperty": property.id.final_iri(),
+            key: { "@value": count, "@type": "xsd:nonNegativeInteger" },
+        })
+    };
+    match (property.min_count, property.max_count) {
+        (Some(min), Some(max)) if min == max => vec![restriction("owl:cardinality", min)],
+        (min, max) => min
+            .map(|min| restriction("owl:minCardinality", min))
+            .into_iter()
+            .chain(max.map(|max| restriction("owl:maxCardinality", max)))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_owl_ontology() {
+        let manifest = TestManifest::new()
+            .with("@id", json!("http://example.com/products"))
+            .with("name", json!("Products"))
+            .with("description", json!("Product catalog"))
+            .with_model("outputFormat", json!("OWL"))
+            .with_instances("baseIRI", json!("http://example.com/data/"))
+            .instance_step(
+                "colors.csv",
+                "PicklistStep",
+                json!({ "instanceType": "Color" }),
+            );
+        let model = model_csv(
+            &["Unit", "Required", "Min Count", "Max Count"],
+            "\
+Product,Product ID,Product,A product,Product ID,Identifier,@id,,,,,
+Product,SKU,Product,A product,SKU,Stock unit,String,,,true,,1
+Product,Tag,Product,A product,Tag,Tags,String,,,,2,5
+Product,Weight,Product,A product,Weight,Weight,Decimal,,kg,,,
+Product,Color,Product,A product,Color,Color,Picklist,Color,,,,
+Color,Color Name,Color,A color,Color Name,Name,@id,,,,,
+",
+        );
+        let run = run_import(
+            &manifest.build(),
+            &[
+                ("model.csv", &model),
+                ("colors.csv", "Color Name\nRed\nBlue\n"),
+            ],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert_eq!(
+            run.model["insert"][0],
+            json!({
+                "@id": "http://example.com/products",
+                "@type": "owl:Ontology",
+                "rdfs:label": "Products",
+                "rdfs:comment": "Product catalog"
+            })
+        );
+
+        let color = run.term("http://example.com/terms/Color").unwrap();
+        assert_eq!(color["@type"], "owl:Class");
+        assert_eq!(
+            color["owl:oneOf"],
+            json!({ "@list": [
+                { "@id": "http://example.com/data/Red" },
+                { "@id": "http://example.com/data/Blue" }
+            ] })
+        );
+
+        let product = run.term("http://example.com/terms/Product").unwrap();
+        assert_eq!(product["@type"], "owl:Class");
+        assert!(product.get("rdfs:range").is_none());
+        let restriction = |property: &str, key: &str, count: usize| {
+            json!({
+                "@type": "owl:Restriction",
+                "owl:onProperty": format!("http://example.com/terms/{}", property),
+                key: { "@value": count, "@type": "xsd:nonNegativeInteger" }
+            })
+        };
+        assert_eq!(
+            product["rdfs:subClassOf"],
+            json!([
+                restriction("sKU", "owl:cardinality", 1),
+                restriction("tag", "owl:minCardinality", 2),
+                restriction("tag", "owl:maxCardinality", 5),
+            ])
+        );
+
+        let sku = run.term("http://example.com/terms/sKU").unwrap();
+        assert_eq!(sku["@type"], "owl:DatatypeProperty");
+        assert_eq!(sku["rdfs:range"], json!(["xsd:string"]));
+        let color = run.term("http://example.com/terms/color").unwrap();
+        assert_eq!(color["@type"], "owl:ObjectProperty");
+        assert_eq!(
+            color["rdfs:range"],
+            json!(["http://example.com/terms/Color"])
+        );
+        let weight = run.term("http://example.com/terms/weight").unwrap();
+        assert_eq!(weight["@type"], "owl:ObjectProperty");
+        assert_eq!(weight["rdfs:range"], json!(["qudt:QuantityValue"]));
+        assert_eq!(weight["qudt:unit"], "http://qudt.org/vocab/unit/KiloGM");
+    }
+}
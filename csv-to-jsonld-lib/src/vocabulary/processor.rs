@@ -1,26 +1,35 @@
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::mem::take;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
-use super::mapping::{MappingConfig, RowValues, VocabularyColumnMapping};
+use super::mapping::{
+    ConstraintCells, LocalizedText, MappingConfig, RowValues, VocabularyColumnMapping,
+};
+use super::ontology::{ontology_vocabulary, parse_ontology, OntologyFormat};
 use crate::error::{ProcessingState, ProcessorError};
 use crate::excel::ExcelReader;
 use crate::manifest::{ImportStep, ModelStep, StepType, StorageLocation};
 use crate::types::{
-    IdOpt, OnEntity, PropertyDatatype, StrictIdOpt, StrictVocabularyMap, VocabularyMap,
-    VocabularyTerm,
+    IdOpt, OnEntity, PropertyDatatype, StrictIdOpt, StrictVocabularyMap, ValueConstraints,
+    VocabularyMap, VocabularyTerm,
+};
+use crate::utils::{
+    canonical_decimal, expand_iri_with_base, expand_prefixed_name, map_xsd_type, resolve_unit,
+    to_pascal_case, undeclared_prefix,
 };
-use crate::utils::{expand_iri_with_base, map_xsd_type, to_pascal_case};
 use crate::{contains_variant, Manifest};
+use regex::Regex;
 
 pub struct VocabularyProcessor {
     manifest: Arc<Manifest>,
     pub vocabulary: VocabularyMap,
     class_properties: HashMap<IdOpt, Vec<String>>,
+    /// IRIs of the base ontology terms that no model CSV has redefined yet
+    imported_terms: HashSet<String>,
     pub(crate) is_strict: bool,
     ignore: HashMap<String, Vec<String>>,
     base_iri: String,
@@ -95,6 +104,7 @@ impl VocabularyProcessor {
             manifest,
             vocabulary: VocabularyMap::new(),
             class_properties: HashMap::new(),
+            imported_terms: HashSet::new(),
             is_strict,
             ignore,
             base_iri,
@@ -124,6 +134,7 @@ impl VocabularyProcessor {
             manifest,
             vocabulary: vocabulary_processor_metadata.vocabulary.into(),
             class_properties,
+            imported_terms: HashSet::new(),
             is_strict,
             ignore: vocabulary_processor_metadata.ignore,
             base_iri: vocabulary_processor_metadata.base_iri,
@@ -209,6 +220,8 @@ impl VocabularyProcessor {
                 .replace_property_id_with(replace_property_id_with)?;
         }
 
+        mapping.column_mapping.detect_localized_columns(headers);
+
         for extra_item in step.extra_items.drain(..) {
             mapping
                 .column_mapping
@@ -274,7 +287,23 @@ impl VocabularyProcessor {
 
         tracing::debug!("Headers: {:#?}", headers);
 
-        let sub_class_of = step.sub_class_of.clone();
+        for parent in step.sub_class_of.iter().flatten() {
+            if let Err(e) = self.check_prefix(parent, "subClassOf") {
+                if self.is_strict {
+                    return Err(e);
+                }
+                self.processing_state.add_warning_from(e);
+            }
+        }
+        let sub_class_of = step.sub_class_of.as_ref().map(|parents| {
+            parents
+                .iter()
+                .map(|parent| {
+                    expand_prefixed_name(&self.manifest.prefixes, parent)
+                        .unwrap_or_else(|| parent.clone())
+                })
+                .collect()
+        });
 
         let mut mapping = self.mapping_config_from_headers(headers, step, self.is_strict)?;
 
@@ -329,6 +358,15 @@ impl VocabularyProcessor {
                 }
             };
 
+            if let Err(e) = self.check_row_prefixes(&row_values, row) {
+                if self.is_strict {
+                    self.processing_state.add_error_from(e);
+                    continue;
+                } else {
+                    self.processing_state.add_warning_from(e);
+                }
+            }
+
             match self.process_class_term(&row_values, sub_class_of.clone()) {
                 Ok(_) => (),
                 Err(e) => {
@@ -376,7 +414,9 @@ impl VocabularyProcessor {
             .class_properties
             .keys()
             .filter_map(|k| {
-                if picklist_classes.contains(&k.normalize().to_pascal_case()) {
+                if picklist_classes.contains(&k.normalize().to_pascal_case())
+                    || picklist_classes.contains(&IdOpt::String(self.class_iri(k).final_iri()))
+                {
                     Some(k.clone())
                 } else {
                     None
@@ -399,14 +439,18 @@ impl VocabularyProcessor {
         // Update class terms with their properties
         for (class_id, properties) in &self.class_properties {
             if let Some(class_term) = self.vocabulary.classes.get_mut(class_id) {
-                class_term.range = Some(
-                    properties
-                        .iter()
-                        .map(|p| {
-                            PropertyDatatype::URI(Some(expand_iri_with_base(&self.base_iri, p)))
-                        })
-                        .collect(),
-                );
+                let mut range = vec![];
+                for property in properties {
+                    let property = PropertyDatatype::URI(Some(
+                        expand_prefixed_name(&self.manifest.prefixes, property)
+                            .unwrap_or_else(|| expand_iri_with_base(&self.base_iri, property)),
+                    ));
+                    // Imported ontology classes may list a property a model CSV also adds
+                    if !range.contains(&property) {
+                        range.push(property);
+                    }
+                }
+                class_term.range = Some(range);
             }
         }
 
@@ -422,6 +466,7 @@ impl VocabularyProcessor {
             class_id,
             class_name,
             class_description,
+            localized,
             extra_items,
             ..
         } = row_values;
@@ -436,40 +481,40 @@ impl VocabularyProcessor {
             }
         }
 
+        let mut class_term = VocabularyTerm {
+            id: self.class_iri(class_id),
+            type_: vec!["rdfs:Class".to_string()],
+            sub_class_of,
+            label: class_name.map(|n| n.to_string()),
+            comment: class_description.map(|d| d.to_string()),
+            localized_labels: localized_texts(localized, LocalizedText::ClassLabel),
+            localized_comments: localized_texts(localized, LocalizedText::ClassComment),
+            domain: None,
+            range: Some(vec![]),
+            unit: None,
+            min_count: None,
+            max_count: None,
+            constraints: ValueConstraints::default(),
+            extra_items: extra_items_result,
+            one_of: None,
+        };
+        if let Some((imported_key, imported)) = self.take_imported(&class_term.id.final_iri(), true)
+        {
+            class_term.inherit(imported);
+            if let Some(properties) = self.class_properties.remove(&imported_key) {
+                self.class_properties
+                    .entry(class_id.clone())
+                    .or_default()
+                    .extend(properties);
+            }
+        }
+
         match self.vocabulary.classes.entry(class_id.clone()) {
             Entry::Vacant(_) => {
-                let class_term = VocabularyTerm {
-                    id: class_id
-                        .normalize()
-                        .to_pascal_case()
-                        .with_base_iri(&self.base_iri),
-                    type_: vec!["rdfs:Class".to_string()],
-                    sub_class_of,
-                    label: class_name.map(|n| n.to_string()),
-                    comment: class_description.map(|d| d.to_string()),
-                    domain: None,
-                    range: Some(vec![]),
-                    extra_items: extra_items_result,
-                    one_of: None,
-                };
-
                 self.vocabulary.classes.insert(class_id.clone(), class_term);
             }
             Entry::Occupied(mut entry) => {
-                entry.get_mut().update_with(VocabularyTerm {
-                    id: class_id
-                        .normalize()
-                        .to_pascal_case()
-                        .with_base_iri(&self.base_iri),
-                    type_: vec!["rdfs:Class".to_string()],
-                    sub_class_of,
-                    label: class_name.map(|n| n.to_string()),
-                    comment: class_description.map(|d| d.to_string()),
-                    domain: None,
-                    range: Some(vec![]),
-                    extra_items: extra_items_result,
-                    one_of: None,
-                })?;
+                entry.get_mut().update_with(class_term)?;
             }
         }
 
@@ -483,7 +528,13 @@ impl VocabularyProcessor {
             property_description,
             property_type,
             property_class,
+            property_unit,
+            property_required,
+            property_min_count,
+            property_max_count,
+            property_constraints,
             class_id,
+            localized,
             extra_items,
             ..
         } = row_values;
@@ -499,12 +550,12 @@ impl VocabularyProcessor {
         let camel_name = property.to_camel_case();
         let range = if !property_class.is_empty() {
             let value = match xsd_type {
-                PropertyDatatype::Picklist(_) => PropertyDatatype::Picklist(Some(
-                    expand_iri_with_base(&self.base_iri, &to_pascal_case(property_class)),
-                )),
-                PropertyDatatype::URI(_) | PropertyDatatype::ID => PropertyDatatype::URI(Some(
-                    expand_iri_with_base(&self.base_iri, &to_pascal_case(property_class)),
-                )),
+                PropertyDatatype::Picklist(_) => {
+                    PropertyDatatype::Picklist(Some(self.target_class_iri(property_class)))
+                }
+                PropertyDatatype::URI(_) | PropertyDatatype::ID => {
+                    PropertyDatatype::URI(Some(self.target_class_iri(property_class)))
+                }
                 _ => {
                     let error_string = format!(
                         "[Property: {}] A property with type {} cannot have a target class ({})",
@@ -515,10 +566,7 @@ impl VocabularyProcessor {
                     } else {
                         self.processing_state
                             .add_warning(error_string, Some("property_processing".to_string()));
-                        PropertyDatatype::URI(Some(expand_iri_with_base(
-                            &self.base_iri,
-                            &to_pascal_case(property_class),
-                        )))
+                        PropertyDatatype::URI(Some(self.target_class_iri(property_class)))
                     }
                 }
             };
@@ -527,6 +575,21 @@ impl VocabularyProcessor {
             Some(vec![xsd_type.clone()])
         };
 
+        let unit = match property_unit {
+            Some(unit) => self.resolve_property_unit(unit, property_name, &xsd_type)?,
+            None => None,
+        };
+
+        let (min_count, max_count) = self.resolve_property_cardinality(
+            *property_required,
+            *property_min_count,
+            *property_max_count,
+            property_name,
+        )?;
+
+        let constraints =
+            self.resolve_value_constraints(property_constraints, property_name, &xsd_type)?;
+
         let mut extra_items_result = HashMap::new();
         for (_, extra_item) in extra_items {
             if matches!(extra_item.on_entity, OnEntity::Property) {
@@ -551,18 +614,22 @@ impl VocabularyProcessor {
         );
 
         // Create property term
-        let property_term = VocabularyTerm {
-            id: camel_name.with_base_iri(&self.base_iri),
+        let mut property_term = VocabularyTerm {
+            id: property
+                .with_prefixes(&self.manifest.prefixes)
+                .unwrap_or_else(|| camel_name.with_base_iri(&self.base_iri)),
             type_: vec!["rdf:Property".to_string()],
             sub_class_of: None,
             label: Some(property_name.to_string()),
             comment: Some(property_desc.to_string()),
-            domain: Some(vec![new_or_existing_class_id
-                .normalize()
-                .to_pascal_case()
-                .with_base_iri(&self.base_iri)
-                .final_iri()]),
+            localized_labels: localized_texts(localized, LocalizedText::PropertyLabel),
+            localized_comments: localized_texts(localized, LocalizedText::PropertyComment),
+            domain: Some(vec![self.class_iri(new_or_existing_class_id).final_iri()]),
             range,
+            unit,
+            min_count,
+            max_count,
+            constraints,
             extra_items: extra_items_result,
             one_of: None,
         };
@@ -574,6 +641,9 @@ impl VocabularyProcessor {
                 property_term,
             );
         } else {
+            if let Some((_, imported)) = self.take_imported(&property_term.id.final_iri(), false) {
+                property_term.inherit(imported);
+            }
             // Otherwise store it in properties map
             match self.vocabulary.properties.entry(camel_name.clone()) {
                 std::collections::hash_map::Entry::Vacant(_) => {
@@ -594,6 +664,269 @@ impl VocabularyProcessor {
         Ok(())
     }
 
+    /// Resolve a property's unit to its QUDT IRI, reporting units that are unknown or declared on
+    /// a non-numeric property
+    fn resolve_property_unit(
+        &mut self,
+        unit: &str,
+        property_name: &str,
+        xsd_type: &PropertyDatatype,
+    ) -> Result<Option<String>, ProcessorError> {
+        let msg = if !matches!(
+            xsd_type,
+            PropertyDatatype::Integer | PropertyDatatype::Long | PropertyDatatype::Decimal
+        ) {
+            format!(
+                "Unit '{}' declared on non-numeric property '{}'",
+                unit, property_name
+            )
+        } else if let Some(unit_iri) = resolve_unit(unit) {
+            return Ok(Some(unit_iri));
+        } else {
+            format!("Unknown unit '{}' for property '{}'", unit, property_name)
+        };
+        if self.is_strict {
+            return Err(ProcessorError::Processing(msg));
+        }
+        self.processing_state.add_warning(
+            format!("{}, ignoring unit", msg),
+            Some("vocabulary_processing".to_string()),
+        );
+        Ok(None)
+    }
+
+    /// Read a property's Required, Min Count and Max Count cells into its value count bounds. A
+    /// required property has a min count of at least one
+    fn resolve_property_cardinality(
+        &mut self,
+        required: Option<&str>,
+        min_count: Option<&str>,
+        max_count: Option<&str>,
+        property_name: &str,
+    ) -> Result<(Option<usize>, Option<usize>), ProcessorError> {
+        let mut invalid = vec![];
+        let is_required = match required.map(|value| value.to_lowercase()).as_deref() {
+            None | Some("false" | "no" | "n" | "0") => false,
+            Some("true" | "yes" | "y" | "1" | "x" | "required") => true,
+            Some(_) => {
+                invalid.push(format!("Required value '{}'", required.unwrap()));
+                false
+            }
+        };
+        let mut parse_count = |kind: &str, value: Option<&str>| {
+            let value = value?;
+            value.parse::<usize>().ok().or_else(|| {
+                invalid.push(format!("{} value '{}'", kind, value));
+                None
+            })
+        };
+        let min_count = parse_count("Min Count", min_count);
+        let max_count = parse_count("Max Count", max_count);
+        let min_count = match (min_count, is_required) {
+            (Some(min_count), true) => Some(min_count.max(1)),
+            (None, true) => Some(1),
+            (min_count, false) => min_count,
+        };
+        if let (Some(min), Some(max)) = (min_count, max_count) {
+            if min > max {
+                invalid.push(format!("min count {} above max count {}", min, max));
+            }
+        }
+        if invalid.is_empty() {
+            return Ok((min_count, max_count));
+        }
+
+        let msg = format!(
+            "Invalid cardinality for property '{}': {}",
+            property_name,
+            invalid.join(", ")
+        );
+        if self.is_strict {
+            return Err(ProcessorError::Processing(msg));
+        }
+        self.processing_state.add_warning(
+            format!("{}, ignoring cardinality", msg),
+            Some("vocabulary_processing".to_string()),
+        );
+        Ok((None, None))
+    }
+
+    /// Read a property's Pattern, Min/Max Value and Min/Max Length cells. Value bounds only apply
+    /// to numeric properties
+    fn resolve_value_constraints(
+        &mut self,
+        cells: &ConstraintCells,
+        property_name: &str,
+        xsd_type: &PropertyDatatype,
+    ) -> Result<ValueConstraints, ProcessorError> {
+        let mut invalid = vec![];
+        if let Some(pattern) = cells.pattern {
+            if let Err(e) = Regex::new(pattern) {
+                invalid.push(format!("Pattern '{}' ({})", pattern, e));
+            }
+        }
+        let is_numeric = matches!(
+            xsd_type,
+            PropertyDatatype::Integer | PropertyDatatype::Long | PropertyDatatype::Decimal
+        );
+        let mut parse_bound = |kind: &str, value: Option<&str>| {
+            let value = value?;
+            match canonical_decimal(value) {
+                Some(bound) if is_numeric => Some(bound),
+                Some(_) => {
+                    invalid.push(format!("{} on a non-numeric property", kind));
+                    None
+                }
+                None => {
+                    invalid.push(format!("{} value '{}'", kind, value));
+                    None
+                }
+            }
+        };
+        let min_value = parse_bound("Min Value", cells.min_value);
+        let max_value = parse_bound("Max Value", cells.max_value);
+        let mut parse_length = |kind: &str, value: Option<&str>| {
+            let value = value?;
+            value.parse::<usize>().ok().or_else(|| {
+                invalid.push(format!("{} value '{}'", kind, value));
+                None
+            })
+        };
+        let min_length = parse_length("Min Length", cells.min_length);
+        let max_length = parse_length("Max Length", cells.max_length);
+        if invalid.is_empty() {
+            return Ok(ValueConstraints {
+                pattern: cells.pattern.map(str::to_string),
+                min_value,
+                max_value,
+                min_length,
+                max_length,
+            });
+        }
+
+        let msg = format!(
+            "Invalid value constraints for property '{}': {}",
+            property_name,
+            invalid.join(", ")
+        );
+        if self.is_strict {
+            return Err(ProcessorError::Processing(msg));
+        }
+        self.processing_state.add_warning(
+            format!("{}, ignoring value constraints", msg),
+            Some("vocabulary_processing".to_string()),
+        );
+        Ok(ValueConstraints::default())
+    }
+
+    /// Seed the vocabulary with the classes and properties of an existing RDFS or OWL ontology,
+    /// which model CSV steps can then extend or override
+    pub async fn import_ontology(
+        &mut self,
+        path: &StorageLocation,
+        s3_client: Option<&aws_sdk_s3::Client>,
+    ) -> Result<ProcessingState, ProcessorError> {
+        let contents = path.read_contents(s3_client).await.map_err(|e| {
+            ProcessorError::Processing(format!("Failed to read ontology @ {}: {}", path, e))
+        })?;
+        let contents = String::from_utf8(contents).map_err(|e| {
+            ProcessorError::Processing(format!("Ontology @ {} is not valid UTF-8: {}", path, e))
+        })?;
+        let graph = parse_ontology(&contents, OntologyFormat::from_path(&path.to_string()))?;
+        let imported = ontology_vocabulary(&graph, &self.base_iri);
+
+        if imported.vocabulary.classes.is_empty() && imported.vocabulary.properties.is_empty() {
+            self.processing_state.add_warning(
+                format!(
+                    "No RDFS or OWL classes or properties found in ontology @ {}",
+                    path
+                ),
+                Some("vocabulary_processing".to_string()),
+            );
+        }
+        for (key, term) in imported.vocabulary.classes {
+            self.imported_terms.insert(term.id.final_iri());
+            self.vocabulary.classes.insert(key, term);
+        }
+        for (key, term) in imported.vocabulary.properties {
+            self.imported_terms.insert(term.id.final_iri());
+            self.vocabulary.properties.insert(key, term);
+        }
+        for (class_id, properties) in imported.class_properties {
+            self.class_properties
+                .entry(class_id)
+                .or_default()
+                .extend(properties);
+        }
+
+        Ok(take(&mut self.processing_state))
+    }
+
+    /// Report the class ID, property ID or target class of a row written as a prefixed name with an
+    /// undeclared prefix, which would otherwise become a term under the base IRI
+    fn check_row_prefixes(&self, row_values: &RowValues, row: usize) -> Result<(), ProcessorError> {
+        let names = [
+            Some(row_values.class_id.final_iri()),
+            row_values.property_id.as_ref().map(IdOpt::final_iri),
+            row_values.property_class.map(str::to_string),
+        ];
+        for name in names.iter().flatten() {
+            self.check_prefix(name, &format!("row {}", row + 1))?;
+        }
+        Ok(())
+    }
+
+    /// An error for a name with an undeclared prefix. Only checked when the manifest declares
+    /// prefixes, so models without any keep their names with colons
+    fn check_prefix(&self, name: &str, location: &str) -> Result<(), ProcessorError> {
+        if self.manifest.prefixes.is_empty() {
+            return Ok(());
+        }
+        match undeclared_prefix(&self.manifest.prefixes, name) {
+            Some(prefix) => Err(ProcessorError::Processing(format!(
+                "Undeclared prefix '{}' in '{}' at {}",
+                prefix, name, location
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// The IRI of a model class: its prefixed name expanded, or its PascalCase name under the
+    /// base IRI
+    fn class_iri(&self, class_id: &IdOpt) -> IdOpt {
+        class_id
+            .with_prefixes(&self.manifest.prefixes)
+            .unwrap_or_else(|| {
+                class_id
+                    .normalize()
+                    .to_pascal_case()
+                    .with_base_iri(&self.base_iri)
+            })
+    }
+
+    /// The IRI of the class a property references, written like a class ID
+    fn target_class_iri(&self, class_name: &str) -> String {
+        expand_prefixed_name(&self.manifest.prefixes, class_name)
+            .unwrap_or_else(|| expand_iri_with_base(&self.base_iri, &to_pascal_case(class_name)))
+    }
+
+    /// Remove the imported term a model CSV term redefines, so the CSV term can take its place
+    fn take_imported(&mut self, iri: &str, is_class: bool) -> Option<(IdOpt, VocabularyTerm)> {
+        if !self.imported_terms.remove(iri) {
+            return None;
+        }
+        let terms = if is_class {
+            &mut self.vocabulary.classes
+        } else {
+            &mut self.vocabulary.properties
+        };
+        let key = terms
+            .iter()
+            .find(|(_, term)| term.id.final_iri() == iri)
+            .map(|(key, _)| key.clone())?;
+        terms.remove_entry(&key)
+    }
+
     fn handle_add_rdfs_label_property(&mut self, class_id: &IdOpt) -> Result<(), ProcessorError> {
         let rdfs_label = "rdfs:label".to_string();
         let rdfs_label_id = IdOpt::String(rdfs_label.clone());
@@ -635,8 +968,14 @@ impl VocabularyProcessor {
                     sub_class_of: None,
                     label: Some("label".to_string()),
                     comment: Some("The human-readable label of the resource".to_string()),
+                    localized_labels: BTreeMap::new(),
+                    localized_comments: BTreeMap::new(),
                     domain: Some(vec![class_id.final_iri()]),
                     range: Some(vec![PropertyDatatype::String]),
+                    unit: None,
+                    min_count: None,
+                    max_count: None,
+                    constraints: ValueConstraints::default(),
                     extra_items: HashMap::new(),
                     one_of: None,
                 };
@@ -651,8 +990,14 @@ impl VocabularyProcessor {
                     sub_class_of: None,
                     label: Some("label".to_string()),
                     comment: Some("The human-readable label of the resource".to_string()),
+                    localized_labels: BTreeMap::new(),
+                    localized_comments: BTreeMap::new(),
                     domain: Some(vec![class_id.final_iri()]),
                     range: Some(vec![PropertyDatatype::String]),
+                    unit: None,
+                    min_count: None,
+                    max_count: None,
+                    constraints: ValueConstraints::default(),
                     extra_items: HashMap::new(),
                     one_of: None,
                 })?;
@@ -669,3 +1014,128 @@ impl VocabularyProcessor {
         (vocabulary, state)
     }
 }
+
+/// The per-language values of one of a row's texts, keyed by language
+fn localized_texts(
+    localized: &[(LocalizedText, String, &str)],
+    text: LocalizedText,
+) -> BTreeMap<String, String> {
+    localized
+        .iter()
+        .filter(|(localized_text, _, _)| *localized_text == text)
+        .map(|(_, language, value)| (language.clone(), value.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::json;
+
+    fn prefixed_manifest() -> TestManifest {
+        TestManifest::new()
+            .with(
+                "prefixes",
+                json!({
+                    "schema": "https://schema.org/",
+                    "skos": "http://www.w3.org/2004/02/skos/core#"
+                }),
+            )
+            .with_instances("baseIRI", json!("http://example.com/data/"))
+            .model_step("parents.csv", json!({ "subClassOf": ["schema:Thing"] }))
+            .instance_step(
+                "organizations.csv",
+                "BasicInstanceStep",
+                json!({ "instanceType": "Organization" }),
+            )
+    }
+
+    #[tokio::test]
+    async fn test_prefixed_model_terms() {
+        let model = model_csv(
+            &[],
+            "\
+schema:Person,ID,Person,A person,ID,Identifier,@id,
+schema:Person,schema:name,Person,A person,Name,Name,String,
+schema:Person,skos:prefLabel,Person,A person,Label,Label,String,
+schema:Person,schema:worksFor,Person,A person,Works For,Employer,URI,schema:Organization
+",
+        );
+        let parents = model_csv(
+            &[],
+            "Organization,Org ID,Organization,An organization,Org ID,Identifier,@id,\n",
+        );
+        let run = run_import(
+            &prefixed_manifest().build(),
+            &[
+                ("model.csv", &model),
+                ("parents.csv", &parents),
+                ("organizations.csv", "Org ID\nACME\n"),
+            ],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert!(run.term("https://schema.org/Person").is_some());
+        assert!(run.term("https://schema.org/name").is_some());
+        assert!(run
+            .term("http://www.w3.org/2004/02/skos/core#prefLabel")
+            .is_some());
+        let works_for = run.term("https://schema.org/worksFor").unwrap();
+        assert_eq!(
+            works_for["rdfs:range"],
+            json!(["https://schema.org/Organization"])
+        );
+        let organization = run.term("http://example.com/terms/Organization").unwrap();
+        assert_eq!(
+            organization["rdfs:subClassOf"],
+            json!(["https://schema.org/Thing"])
+        );
+
+        assert_eq!(run.model["@context"]["schema"], "https://schema.org/");
+        assert_eq!(run.instances["@context"]["schema"], "https://schema.org/");
+        assert_eq!(
+            run.instances["@context"]["skos"],
+            "http://www.w3.org/2004/02/skos/core#"
+        );
+        assert_eq!(
+            run.instances["@context"]["Name"]["@id"],
+            "https://schema.org/name"
+        );
+        assert_eq!(
+            run.instance("ACME").unwrap()["@type"],
+            json!(["Organization"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undeclared_prefixes_are_reported() {
+        let model = model_csv(
+            &[],
+            "\
+schema:Person,ID,Person,A person,ID,Identifier,@id,
+schema:Person,dc:title,Person,A person,Title,Title,String,
+",
+        );
+        let parents = model_csv(
+            &[],
+            "Organization,Org ID,Organization,An organization,Org ID,Identifier,@id,\n",
+        );
+        let manifest =
+            prefixed_manifest().model_step("others.csv", json!({ "subClassOf": ["foaf:Agent"] }));
+        let files = [
+            ("model.csv", model.as_str()),
+            ("parents.csv", parents.as_str()),
+            ("others.csv", parents.as_str()),
+            ("organizations.csv", "Org ID\nACME\n"),
+        ];
+
+        let run = run_import(&manifest.build(), &files, false).await;
+        assert!(run.has_warning("Undeclared prefix 'dc' in 'dc:title' at row 2"));
+        assert!(run.has_warning("Undeclared prefix 'foaf' in 'foaf:Agent' at subClassOf"));
+
+        let run = run_import(&manifest.build(), &files, true).await;
+        assert!(run.has_error("Undeclared prefix 'dc' in 'dc:title' at row 2"));
+    }
+}
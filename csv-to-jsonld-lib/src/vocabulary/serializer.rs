@@ -3,8 +3,11 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::error::ProcessorError;
-use crate::manifest::StorageLocation;
+use crate::manifest::{ModelOutputFormat, StorageLocation};
 use crate::types::{FlureeDataModel, JsonLdVocabulary, StrictVocabularyMap, VocabularyMap};
+
+use super::owl::owl_ontology;
+use super::shapes::node_shapes;
 use crate::Manifest;
 
 pub struct VocabularySerializer {
@@ -22,6 +25,13 @@ impl VocabularySerializer {
         output_path: &StorageLocation,
         s3_client: Option<&aws_sdk_s3::Client>,
     ) -> Result<(), ProcessorError> {
+        if self.manifest.model.output_format == ModelOutputFormat::Owl {
+            return self
+                .save_ontology(&vocabulary, output_path, s3_client)
+                .await;
+        }
+
+        let has_units = has_units(&vocabulary);
         let ledger = self.manifest.ledger.clone();
         let label = self.manifest.name.clone();
         let comment = self.manifest.description.clone();
@@ -36,16 +46,19 @@ impl VocabularySerializer {
         };
 
         let vocabulary = JsonLdVocabulary {
-            context: serde_json::json!({
-                "rdfs": "http://www.w3.org/2000/01/rdf-schema#",
-                "rdf": "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
-                "xsd": "http://www.w3.org/2001/XMLSchema#",
-                "f": "https://ns.flur.ee/ledger#",
-                "rdfs:domain": { "@type": "@id" },
-                "rdfs:range": { "@type": "@id" },
-                "rdfs:subClassOf": { "@type": "@id" },
-                "f:oneOf": { "@type": "@id" },
-            }),
+            context: self.with_prefixes(with_units(
+                serde_json::json!({
+                    "rdfs": "http://www.w3.org/2000/01/rdf-schema#",
+                    "rdf": "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+                    "xsd": "http://www.w3.org/2001/XMLSchema#",
+                    "f": "https://ns.flur.ee/ledger#",
+                    "rdfs:domain": { "@type": "@id" },
+                    "rdfs:range": { "@type": "@id" },
+                    "rdfs:subClassOf": { "@type": "@id" },
+                    "f:oneOf": { "@type": "@id" },
+                }),
+                has_units,
+            )),
             ledger,
             insert,
         };
@@ -93,4 +106,94 @@ impl VocabularySerializer {
 
         // Ok(())
     }
+
+    /// Write the vocabulary as an OWL ontology
+    async fn save_ontology(
+        &self,
+        vocabulary: &VocabularyMap,
+        output_path: &StorageLocation,
+        s3_client: Option<&aws_sdk_s3::Client>,
+    ) -> Result<(), ProcessorError> {
+        let ontology = serde_json::json!({
+            "@context": self.with_prefixes(with_units(
+                serde_json::json!({
+                    "rdfs": "http://www.w3.org/2000/01/rdf-schema#",
+                    "rdf": "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+                    "owl": "http://www.w3.org/2002/07/owl#",
+                    "xsd": "http://www.w3.org/2001/XMLSchema#",
+                    "rdfs:domain": { "@type": "@id" },
+                    "rdfs:range": { "@type": "@id" },
+                    "rdfs:subClassOf": { "@type": "@id" },
+                    "owl:onProperty": { "@type": "@id" },
+                }),
+                has_units(vocabulary),
+            )),
+            "ledger": self.manifest.ledger,
+            "insert": owl_ontology(&self.manifest, vocabulary)?,
+        });
+
+        let ontology_json = serde_json::to_vec_pretty(&ontology).map_err(|e| {
+            ProcessorError::Processing(format!("Failed to serialize ontology: {}", e))
+        })?;
+        output_path.write_contents(&ontology_json, s3_client).await
+    }
+
+    /// Write the vocabulary's SHACL node shapes as a separate transaction
+    pub async fn save_shapes(
+        &self,
+        vocabulary: &VocabularyMap,
+        output_path: &StorageLocation,
+        s3_client: Option<&aws_sdk_s3::Client>,
+    ) -> Result<(), ProcessorError> {
+        let shapes = serde_json::json!({
+            "@context": self.with_prefixes(with_units(
+                serde_json::json!({
+                    "sh": "http://www.w3.org/ns/shacl#",
+                    "rdf": "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+                    "xsd": "http://www.w3.org/2001/XMLSchema#",
+                    "sh:targetClass": { "@type": "@id" },
+                    "sh:path": { "@type": "@id" },
+                    "sh:datatype": { "@type": "@id" },
+                    "sh:class": { "@type": "@id" },
+                    "sh:nodeKind": { "@type": "@id" },
+                }),
+                has_units(vocabulary),
+            )),
+            "ledger": self.manifest.ledger,
+            "insert": node_shapes(vocabulary),
+        });
+
+        let shapes_json = serde_json::to_vec_pretty(&shapes).map_err(|e| {
+            ProcessorError::Processing(format!("Failed to serialize shapes: {}", e))
+        })?;
+        output_path.write_contents(&shapes_json, s3_client).await
+    }
+
+    /// Declare the manifest's prefixes in a context, alongside its built-in ones
+    fn with_prefixes(&self, mut context: serde_json::Value) -> serde_json::Value {
+        if let Some(context) = context.as_object_mut() {
+            for (prefix, namespace) in &self.manifest.prefixes {
+                context
+                    .entry(prefix.clone())
+                    .or_insert_with(|| serde_json::Value::String(namespace.clone()));
+            }
+        }
+        context
+    }
+}
+
+fn has_units(vocabulary: &VocabularyMap) -> bool {
+    vocabulary
+        .properties
+        .values()
+        .any(|prop| prop.unit.is_some())
+}
+
+/// Declare the QUDT namespace, and that `qudt:unit` values are IRIs, when some property has a unit
+fn with_units(mut context: serde_json::Value, has_units: bool) -> serde_json::Value {
+    if has_units {
+        context["qudt"] = serde_json::json!("http://qudt.org/schema/qudt/");
+        context["qudt:unit"] = serde_json::json!({ "@type": "@id" });
+    }
+    context
 }
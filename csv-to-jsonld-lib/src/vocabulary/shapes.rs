@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::types::{PropertyDatatype, VocabularyMap, VocabularyTerm};
+
+/// Build one `sh:NodeShape` per class, with a property shape for each property in the class's
+/// range. Ranges, picklist values, cardinality and value constraints become SHACL constraints
+pub(crate) fn node_shapes(vocabulary: &VocabularyMap) -> Vec<JsonValue> {
+    let properties: HashMap<String, &VocabularyTerm> = vocabulary
+        .properties
+        .values()
+        .map(|property| (property.id.final_iri(), property))
+        .collect();
+
+    let mut classes: Vec<&VocabularyTerm> = vocabulary.classes.values().collect();
+    classes.sort_by_key(|class| class.id.final_iri());
+
+    classes
+        .into_iter()
+        .map(|class| {
+            let class_iri = class.id.final_iri();
+            let property_shapes: Vec<JsonValue> = class
+                .range
+                .iter()
+                .flatten()
+                .filter_map(|range| match range {
+                    PropertyDatatype::URI(Some(iri)) => properties.get(iri),
+                    _ => None,
+                })
+                .filter_map(|property| property_shape(property, vocabulary))
+                .collect();
+
+            let mut shape = Map::new();
+            shape.insert("@id".to_string(), json!(format!("{}Shape", class_iri)));
+            shape.insert("@type".to_string(), json!("sh:NodeShape"));
+            shape.insert("sh:targetClass".to_string(), json!(class_iri));
+            if !property_shapes.is_empty() {
+                shape.insert("sh:property".to_string(), json!(property_shapes));
+            }
+            JsonValue::Object(shape)
+        })
+        .collect()
+}
+
+/// The property shape of a property, or None for the identifier properties that become `@id`
+fn property_shape(property: &VocabularyTerm, vocabulary: &VocabularyMap) -> Option<JsonValue> {
+    let range = property.range.as_ref().and_then(|range| range.first());
+    if matches!(range, Some(PropertyDatatype::ID)) {
+        return None;
+    }
+
+    let mut shape = Map::new();
+    shape.insert("sh:path".to_string(), json!(property.id.final_iri()));
+    if let Some(min_count) = property.min_count {
+        shape.insert("sh:minCount".to_string(), json!(min_count));
+    }
+    if let Some(max_count) = property.max_count {
+        shape.insert("sh:maxCount".to_string(), json!(max_count));
+    }
+
+    // Values with a unit are quantity nodes, so their bounds apply to the numeric value
+    if property.unit.is_some() {
+        shape.insert("sh:class".to_string(), json!("qudt:QuantityValue"));
+        let bounds = value_bounds(property);
+        if !bounds.is_empty() {
+            let mut numeric_value = bounds;
+            numeric_value.insert("sh:path".to_string(), json!("qudt:numericValue"));
+            shape.insert(
+                "sh:node".to_string(),
+                json!({ "@type": "sh:NodeShape", "sh:property": numeric_value }),
+            );
+        }
+        return Some(JsonValue::Object(shape));
+    }
+
+    match range {
+        Some(PropertyDatatype::URI(Some(class_iri))) => {
+            shape.insert("sh:class".to_string(), json!(class_iri));
+        }
+        Some(PropertyDatatype::Picklist(Some(class_iri))) => {
+            shape.insert("sh:class".to_string(), json!(class_iri));
+            let picklist_values = vocabulary
+                .classes
+                .values()
+                .find(|class| &class.id.final_iri() == class_iri)
+                .and_then(|class| class.one_of.as_ref());
+            if let Some(values) = picklist_values {
+                let values: Vec<JsonValue> = values
+                    .iter()
+                    .map(|value| json!({ "@id": value.final_iri() }))
+                    .collect();
+                shape.insert("sh:in".to_string(), json!({ "@list": values }));
+            }
+        }
+        Some(PropertyDatatype::URI(None) | PropertyDatatype::Picklist(None)) => {
+            shape.insert("sh:nodeKind".to_string(), json!("sh:IRI"));
+        }
+        Some(datatype) => {
+            shape.insert("sh:datatype".to_string(), json!(datatype));
+        }
+        None => {}
+    }
+
+    let constraints = &property.constraints;
+    if let Some(pattern) = &constraints.pattern {
+        // Model patterns match whole values, SHACL patterns match anywhere in the value
+        shape.insert(
+            "sh:pattern".to_string(),
+            json!(format!("^(?:{})$", pattern)),
+        );
+    }
+    if let Some(min_length) = constraints.min_length {
+        shape.insert("sh:minLength".to_string(), json!(min_length));
+    }
+    if let Some(max_length) = constraints.max_length {
+        shape.insert("sh:maxLength".to_string(), json!(max_length));
+    }
+    shape.extend(value_bounds(property));
+
+    Some(JsonValue::Object(shape))
+}
+
+/// `sh:minInclusive` and `sh:maxInclusive` for a property's value bounds
+fn value_bounds(property: &VocabularyTerm) -> Map<String, JsonValue> {
+    let bounds = [
+        ("sh:minInclusive", &property.constraints.min_value),
+        ("sh:maxInclusive", &property.constraints.max_value),
+    ];
+    bounds
+        .into_iter()
+        .filter_map(|(key, bound)| {
+            let bound = bound.as_ref()?;
+            Some((
+                key.to_string(),
+                json!({ "@value": bound, "@type": "xsd:decimal" }),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processor::testing::{model_csv, run_import, TestManifest};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_node_shapes() {
+        let manifest = TestManifest::new()
+            .with_instances("baseIRI", json!("http://example.com/data/"))
+            .instance_step(
+                "colors.csv",
+                "PicklistStep",
+                json!({ "instanceType": "Color" }),
+            );
+        let model = model_csv(
+            &[
+                "Unit",
+                "Required",
+                "Max Count",
+                "Pattern",
+                "Min Value",
+                "Max Value",
+                "Min Length",
+                "Max Length",
+            ],
+            "\
+Product,Product ID,Product,A product,Product ID,Identifier,@id,,,,,,,,,
+Product,SKU,Product,A product,SKU,Stock unit,String,,,true,1,[A-Z]{3}-\\d+,,,3,12
+Product,Rating,Product,A product,Rating,Stars,Integer,,,,,,1,5,,
+Product,Weight,Product,A product,Weight,Weight,Decimal,,kg,,,,0,,,
+Product,Color,Product,A product,Color,Color,Picklist,Color,,,,,,,,
+Color,Color Name,Color,A color,Color Name,Name,@id,,,,,,,,,
+",
+        );
+        let run = run_import(
+            &manifest.build(),
+            &[
+                ("model.csv", &model),
+                ("colors.csv", "Color Name\nRed\nBlue\n"),
+            ],
+            true,
+        )
+        .await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert_eq!(
+            run.shapes["@context"]["qudt"],
+            "http://qudt.org/schema/qudt/"
+        );
+        let shapes = run.shapes["insert"].as_array().unwrap();
+        assert_eq!(
+            shapes[0],
+            json!({
+                "@id": "http://example.com/terms/ColorShape",
+                "@type": "sh:NodeShape",
+                "sh:targetClass": "http://example.com/terms/Color"
+            })
+        );
+        let product = &shapes[1];
+        assert_eq!(
+            product["sh:targetClass"],
+            "http://example.com/terms/Product"
+        );
+        assert_eq!(
+            product["sh:property"],
+            json!([
+                {
+                    "sh:path": "http://example.com/terms/sKU",
+                    "sh:datatype": "xsd:string",
+                    "sh:minCount": 1,
+                    "sh:maxCount": 1,
+                    "sh:pattern": "^(?:[A-Z]{3}-\\d+)$",
+                    "sh:minLength": 3,
+                    "sh:maxLength": 12
+                },
+                {
+                    "sh:path": "http://example.com/terms/rating",
+                    "sh:datatype": "xsd:integer",
+                    "sh:minInclusive": { "@value": "1", "@type": "xsd:decimal" },
+                    "sh:maxInclusive": { "@value": "5", "@type": "xsd:decimal" }
+                },
+                {
+                    "sh:path": "http://example.com/terms/weight",
+                    "sh:class": "qudt:QuantityValue",
+                    "sh:node": {
+                        "@type": "sh:NodeShape",
+                        "sh:property": {
+                            "sh:path": "qudt:numericValue",
+                            "sh:minInclusive": { "@value": "0", "@type": "xsd:decimal" }
+                        }
+                    }
+                },
+                {
+                    "sh:path": "http://example.com/terms/color",
+                    "sh:class": "http://example.com/terms/Color",
+                    "sh:in": { "@list": [
+                        { "@id": "http://example.com/data/Red" },
+                        { "@id": "http://example.com/data/Blue" }
+                    ] }
+                }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_units_are_only_declared_when_used() {
+        let model = model_csv(
+            &[],
+            "Product,Weight,Product,A product,Weight,Weight,Decimal,\n",
+        );
+        let run = run_import(&TestManifest::new().build(), &[("model.csv", &model)], true).await;
+
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        for context in [&run.model["@context"], &run.shapes["@context"]] {
+            assert!(context.get("qudt").is_none(), "{context}");
+            assert!(context.get("qudt:unit").is_none(), "{context}");
+        }
+    }
+}